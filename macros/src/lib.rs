@@ -0,0 +1,190 @@
+//! Procedural macros for [`str-cat`](https://docs.rs/str-cat), used from
+//! there with the `fmt` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{bracketed, parse_macro_input, Expr, Ident, LitStr, Token};
+
+/// The implementation of `cat_fmt!` in [`str-cat`](https://docs.rs/str-cat),
+/// which passes its own path in brackets first, so that the expansion names
+/// `str_cat!` through it even when the crate is renamed.
+#[proc_macro]
+pub fn cat_fmt(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as Input);
+    match expand(input) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+struct Input {
+    krate: TokenStream2,
+    target: Option<Expr>,
+    fmt: LitStr,
+    args: Vec<Expr>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let krate;
+        bracketed!(krate in input);
+        let krate = krate.parse()?;
+
+        let fork = input.fork();
+        let target = if fork.parse::<LitStr>().is_ok() && (fork.is_empty() || fork.peek(Token![,]))
+        {
+            None
+        } else {
+            let target = input.parse()?;
+            input.parse::<Token![;]>()?;
+            Some(target)
+        };
+
+        let fmt = input.parse()?;
+        let mut args = vec![];
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            args.push(input.parse()?);
+        }
+        Ok(Self {
+            krate,
+            target,
+            fmt,
+            args,
+        })
+    }
+}
+
+enum Arg {
+    Index(usize),
+    Name(Ident),
+}
+
+enum Segment {
+    Text(String),
+    Hole { arg: Arg, spec: Option<String> },
+}
+
+/// Split `fmt` into text and holes, numbering `{}` holes in order.
+fn parse_segments(fmt: &LitStr) -> syn::Result<Vec<Segment>> {
+    let error = |message: &str| syn::Error::new(fmt.span(), message);
+    let value = fmt.value();
+    let mut segments = vec![];
+    let mut text = String::new();
+    let mut next_index = 0;
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '}' => return Err(error("unmatched `}` in format string")),
+            '{' => {
+                let mut hole = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => hole.push(c),
+                        None => return Err(error("unmatched `{` in format string")),
+                    }
+                }
+                let (arg, spec) = match hole.split_once(':') {
+                    Some((arg, spec)) => (arg.trim(), Some(spec.to_owned())),
+                    None => (hole.trim(), None),
+                };
+                let arg = if arg.is_empty() {
+                    next_index += 1;
+                    Arg::Index(next_index - 1)
+                } else if let Ok(index) = arg.parse() {
+                    Arg::Index(index)
+                } else {
+                    let name = syn::parse_str::<Ident>(arg).map_err(|_| {
+                        error(&format!("invalid argument `{arg}` in format string"))
+                    })?;
+                    Arg::Name(Ident::new(&name.to_string(), fmt.span()))
+                };
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Hole { arg, spec });
+            }
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+fn expand(input: Input) -> syn::Result<TokenStream2> {
+    let Input {
+        krate,
+        target,
+        fmt,
+        args,
+    } = input;
+    let segments = parse_segments(&fmt)?;
+
+    let bindings: Vec<_> = (0..args.len())
+        .map(|i| format_ident!("arg{}", i, span = Span::mixed_site()))
+        .collect();
+    let mut used = vec![false; args.len()];
+    let mut pieces = vec![];
+    for segment in segments {
+        let piece = match segment {
+            Segment::Text(text) => {
+                let text = LitStr::new(&text, fmt.span());
+                quote!(#text)
+            }
+            Segment::Hole { arg, spec } => {
+                let value = match arg {
+                    Arg::Index(index) => {
+                        let binding = bindings.get(index).ok_or_else(|| {
+                            syn::Error::new(
+                                fmt.span(),
+                                format!("invalid reference to positional argument {index}"),
+                            )
+                        })?;
+                        used[index] = true;
+                        quote!(#binding)
+                    }
+                    Arg::Name(name) => quote!(#name),
+                };
+                match spec {
+                    Some(spec) => {
+                        let spec = LitStr::new(&format!("{{:{spec}}}"), fmt.span());
+                        quote!(@display(::core::format_args!(#spec, #value)))
+                    }
+                    None => quote!(@display(#value)),
+                }
+            }
+        };
+        pieces.push(piece);
+    }
+    if let Some(index) = used.iter().position(|&used| !used) {
+        return Err(syn::Error::new_spanned(&args[index], "argument never used"));
+    }
+
+    let target = target.map(|target| quote!(#target;));
+    let output = quote!(#krate::str_cat!(#target #(#pieces),*));
+    if args.is_empty() {
+        Ok(output)
+    } else {
+        Ok(quote! {
+            match (#(&#args,)*) {
+                (#(#bindings,)*) => #output,
+            }
+        })
+    }
+}
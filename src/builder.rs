@@ -0,0 +1,105 @@
+use crate::len::add_len;
+
+/// A builder for a [`String`](String) assembled across multiple statements or
+/// branches, which counts the pieces like [`str_cat`](crate::str_cat) and
+/// reserves the capacity once when finished.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::StrCat;
+///
+/// let name = "World".to_owned();
+/// let loud = true;
+///
+/// let mut cat = StrCat::with_capacity(64);
+/// cat.s("Hello, ").s(&name);
+/// if loud {
+///     cat.c('!');
+/// }
+/// let s = cat.finish();
+/// assert_eq!(s, "Hello, World!");
+/// assert!(s.capacity() >= 64);
+///
+/// // Appending to an existing string.
+/// let mut s = "> ".to_owned();
+/// StrCat::new().s("a").c('b').finish_into(&mut s);
+/// assert_eq!(s, "> ab");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StrCat<'a> {
+    pieces: Vec<Part<'a>>,
+    len: usize,
+    capacity: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Part<'a> {
+    Str(&'a str),
+    Char(char),
+}
+
+impl<'a> StrCat<'a> {
+    /// Create an empty builder.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty builder, whose [`finish`](Self::finish) returns a
+    /// string with at least `capacity` bytes of capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    /// Add a string piece.
+    #[inline]
+    pub fn s(&mut self, s: &'a str) -> &mut Self {
+        self.len = add_len(self.len, s.len());
+        self.pieces.push(Part::Str(s));
+        self
+    }
+
+    /// Add a character.
+    #[inline]
+    pub fn c(&mut self, c: char) -> &mut Self {
+        self.len = add_len(self.len, c.len_utf8());
+        self.pieces.push(Part::Char(c));
+        self
+    }
+
+    /// The number of bytes added so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether nothing but empty pieces have been added.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Concatenate the pieces into a new string.
+    #[inline]
+    pub fn finish(&self) -> String {
+        let mut s = String::with_capacity(self.capacity.max(self.len));
+        self.finish_into(&mut s);
+        s
+    }
+
+    /// Append the pieces to `s`, reserving the capacity once.
+    pub fn finish_into(&self, s: &mut String) {
+        s.reserve(self.len);
+        for piece in &self.pieces {
+            match *piece {
+                Part::Str(piece) => s.push_str(piece),
+                Part::Char(c) => s.push(c),
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::piece::Piece;
+
+/// A piece mapped to upper or lower case while being copied, with a fast path
+/// for ASCII.
+///
+/// Non-ASCII characters are mapped one by one like
+/// [`char::to_uppercase`](char::to_uppercase), so unlike
+/// [`str::to_lowercase`](str::to_lowercase), a final sigma is not treated
+/// specially.
+pub struct CaseMapped<'a> {
+    value: &'a str,
+    upper: bool,
+    len: usize,
+}
+
+impl<'a> CaseMapped<'a> {
+    #[inline]
+    pub fn upper(value: &'a str) -> Self {
+        Self::new(value, true)
+    }
+
+    #[inline]
+    pub fn lower(value: &'a str) -> Self {
+        Self::new(value, false)
+    }
+
+    #[inline]
+    fn new(value: &'a str, upper: bool) -> Self {
+        let len = if value.is_ascii() {
+            value.len()
+        } else {
+            let mut len = 0;
+            for_each_mapped(value, upper, |c| len += c.len_utf8());
+            len
+        };
+        Self { value, upper, len }
+    }
+}
+
+impl Piece for CaseMapped<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn emit<F: FnMut(&str)>(&self, mut push_str: F) {
+        if self.value.is_ascii() {
+            let mut buf = [0; 64];
+            for chunk in self.value.as_bytes().chunks(buf.len()) {
+                let buf = &mut buf[..chunk.len()];
+                buf.copy_from_slice(chunk);
+                if self.upper {
+                    buf.make_ascii_uppercase();
+                } else {
+                    buf.make_ascii_lowercase();
+                }
+                // ASCII stays ASCII after case mapping.
+                push_str(std::str::from_utf8(buf).unwrap());
+            }
+        } else {
+            let mut buf = [0; 4];
+            for_each_mapped(self.value, self.upper, |c| {
+                push_str(c.encode_utf8(&mut buf))
+            });
+        }
+    }
+}
+
+#[inline]
+fn for_each_mapped<F: FnMut(char)>(value: &str, upper: bool, f: F) {
+    if upper {
+        value.chars().flat_map(char::to_uppercase).for_each(f);
+    } else {
+        value.chars().flat_map(char::to_lowercase).for_each(f);
+    }
+}
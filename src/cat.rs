@@ -0,0 +1,84 @@
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use crate::len::add_len;
+
+/// Options of the concatenating macros, filled in from the `key = value`
+/// items after the target.
+#[derive(Default)]
+pub struct CatOptions {
+    /// Reserved on top of the counted length, for pushing more later.
+    pub reserve_extra: usize,
+    /// Whether the buffer is shrunk to its length in the end.
+    pub fit: bool,
+}
+
+/// A type that [`cat`](crate::cat) can build, which is inferred from the
+/// context.
+///
+/// Implementations reserve the total length of the pieces once, and push them
+/// the way the dedicated macro of the type does, so a [`PathBuf`] is built
+/// from components like [`path_cat`](crate::path_cat).
+pub trait CatTarget: Sized {
+    /// The borrowed form of the pieces.
+    type Piece: ?Sized;
+
+    /// Concatenate `pieces` into a new value.
+    fn from_pieces(pieces: &[&Self::Piece]) -> Self;
+}
+
+impl CatTarget for String {
+    type Piece = str;
+
+    fn from_pieces(pieces: &[&str]) -> Self {
+        let len = pieces.iter().map(|piece| piece.len()).fold(0, add_len);
+        let mut s = String::with_capacity(len);
+        for piece in pieces {
+            s.push_str(piece);
+        }
+        s
+    }
+}
+
+impl CatTarget for OsString {
+    type Piece = OsStr;
+
+    fn from_pieces(pieces: &[&OsStr]) -> Self {
+        let len = pieces.iter().map(|piece| piece.len()).fold(0, add_len);
+        let mut s = OsString::with_capacity(len);
+        for piece in pieces {
+            s.push(piece);
+        }
+        s
+    }
+}
+
+impl CatTarget for PathBuf {
+    type Piece = Path;
+
+    fn from_pieces(pieces: &[&Path]) -> Self {
+        // Counting the separator that `PathBuf::push` may insert before it.
+        let len = pieces
+            .iter()
+            .map(|piece| piece.as_os_str().len() + 1)
+            .fold(0, add_len);
+        let mut path = PathBuf::with_capacity(len);
+        for piece in pieces {
+            path.push(piece);
+        }
+        path
+    }
+}
+
+impl<T: Clone> CatTarget for Vec<T> {
+    type Piece = [T];
+
+    fn from_pieces(pieces: &[&[T]]) -> Self {
+        let len = pieces.iter().map(|piece| piece.len()).fold(0, add_len);
+        let mut v = Vec::with_capacity(len);
+        for piece in pieces {
+            v.extend_from_slice(piece);
+        }
+        v
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt::{self, Write};
+
+use crate::len::add_len;
+
+/// The pieces of a string which is never concatenated, as returned by
+/// [`chain_cat`](crate::chain_cat).
+///
+/// It keeps nothing but references to the pieces, so comparing, iterating or
+/// writing it to a sink takes no allocation. [`String::from`](String::from)
+/// concatenates it into a string with the exact capacity.
+#[derive(Clone, Copy)]
+pub struct Chain<'a> {
+    #[doc(hidden)]
+    pub __pieces: &'a [&'a str],
+}
+
+impl<'a> Chain<'a> {
+    /// Create a chain of `pieces`.
+    #[inline]
+    pub fn new(pieces: &'a [&'a str]) -> Self {
+        Self { __pieces: pieces }
+    }
+
+    /// The pieces.
+    #[inline]
+    pub fn pieces(&self) -> &'a [&'a str] {
+        self.__pieces
+    }
+
+    /// The number of bytes in all pieces.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.__pieces
+            .iter()
+            .map(|piece| piece.len())
+            .fold(0, add_len)
+    }
+
+    /// Whether all pieces are empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.__pieces.iter().all(|piece| piece.is_empty())
+    }
+
+    /// An iterator over the bytes of all pieces.
+    #[inline]
+    pub fn bytes(&self) -> impl DoubleEndedIterator<Item = u8> + 'a {
+        self.__pieces.iter().flat_map(|piece| piece.bytes())
+    }
+
+    /// An iterator over the `char`s of all pieces.
+    #[inline]
+    pub fn chars(&self) -> impl DoubleEndedIterator<Item = char> + 'a {
+        self.__pieces.iter().flat_map(|piece| piece.chars())
+    }
+
+    /// Append all pieces to `s`, reserving the capacity once.
+    pub fn push_to(&self, s: &mut String) {
+        s.reserve(self.len());
+        for piece in self.__pieces {
+            s.push_str(piece);
+        }
+    }
+}
+
+impl fmt::Display for Chain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.__pieces
+            .iter()
+            .try_for_each(|piece| f.write_str(piece))
+    }
+}
+
+impl fmt::Debug for Chain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.chars() {
+            for c in c.escape_debug() {
+                f.write_char(c)?;
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+impl From<Chain<'_>> for String {
+    #[inline]
+    fn from(chain: Chain<'_>) -> Self {
+        let mut s = String::new();
+        chain.push_to(&mut s);
+        s
+    }
+}
+
+impl PartialEq<str> for Chain<'_> {
+    fn eq(&self, mut other: &str) -> bool {
+        for piece in self.__pieces {
+            match other.strip_prefix(piece) {
+                Some(rest) => other = rest,
+                None => return false,
+            }
+        }
+        other.is_empty()
+    }
+}
+
+impl PartialEq<&str> for Chain<'_> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<String> for Chain<'_> {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<Chain<'_>> for str {
+    #[inline]
+    fn eq(&self, other: &Chain<'_>) -> bool {
+        *other == *self
+    }
+}
+
+impl PartialEq<Chain<'_>> for &str {
+    #[inline]
+    fn eq(&self, other: &Chain<'_>) -> bool {
+        *other == **self
+    }
+}
+
+impl PartialEq<Chain<'_>> for String {
+    #[inline]
+    fn eq(&self, other: &Chain<'_>) -> bool {
+        *other == **self
+    }
+}
+
+impl PartialEq for Chain<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for Chain<'_> {}
+
+/// Serialized as a string, which is written through
+/// [`collect_str`](serde::Serializer::collect_str) without concatenating the
+/// pieces first, unless the serializer needs to.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Chain<'_> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
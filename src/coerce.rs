@@ -0,0 +1,253 @@
+use std::borrow::Cow;
+use std::ffi::{CStr, OsStr};
+use std::ops::Deref;
+use std::path::Path;
+
+/// Something that dereferences to `str` through any number of `Deref`s, where
+/// `M` is inferred to count them, so that types that can't be used as pieces
+/// are reported as such instead of as a type mismatch.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be used as a string piece",
+    label = "expected something that dereferences to `str`",
+    note = "convert it with `.to_string()`, or write it as `display(value)` to format it"
+)]
+pub trait StrPiece<M> {
+    fn as_str(&self) -> &str;
+}
+
+impl StrPiece<()> for str {
+    #[inline]
+    fn as_str(&self) -> &str {
+        self
+    }
+}
+
+pub struct Derefs<M>(M);
+
+impl<M, T: ?Sized + Deref> StrPiece<Derefs<M>> for T
+where
+    T::Target: StrPiece<M>,
+{
+    #[inline]
+    fn as_str(&self) -> &str {
+        (**self).as_str()
+    }
+}
+
+#[inline]
+pub fn as_str<M, T: ?Sized + StrPiece<M>>(value: &T) -> &str {
+    value.as_str()
+}
+
+pub struct ViaAsRef;
+pub struct ViaCStr;
+#[cfg(unix)]
+pub struct ViaBytes<M>(M);
+#[cfg(unix)]
+pub struct ViaOsStr;
+
+/// A byte slice or array, which is taken as raw bytes for an `OsStr` on Unix
+/// rather than through `AsRef<[u8]>`, since `str` implements that too.
+#[cfg(unix)]
+pub trait RawBytes {
+    fn raw_bytes(&self) -> &[u8];
+}
+
+#[cfg(unix)]
+impl RawBytes for [u8] {
+    #[inline]
+    fn raw_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(unix)]
+impl<const N: usize> RawBytes for [u8; N] {
+    #[inline]
+    fn raw_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Something that can be used as an `OsStr` piece, where `M` tells whether it
+/// is a C string, whose bytes are taken without the NUL on Unix, or raw bytes
+/// on Unix.
+pub trait OsStrPiece<M> {
+    fn as_os_str(&self) -> &OsStr;
+}
+
+impl<T: ?Sized + AsRef<OsStr>> OsStrPiece<ViaAsRef> for T {
+    #[inline]
+    fn as_os_str(&self) -> &OsStr {
+        self.as_ref()
+    }
+}
+
+#[cfg(unix)]
+impl<T: ?Sized + Deref<Target = CStr>> OsStrPiece<ViaCStr> for T {
+    #[inline]
+    fn as_os_str(&self) -> &OsStr {
+        std::os::unix::ffi::OsStrExt::from_bytes(self.to_bytes())
+    }
+}
+
+#[cfg(unix)]
+impl<T: ?Sized + RawBytes> OsStrPiece<ViaBytes<()>> for T {
+    #[inline]
+    fn as_os_str(&self) -> &OsStr {
+        std::os::unix::ffi::OsStrExt::from_bytes(self.raw_bytes())
+    }
+}
+
+#[cfg(unix)]
+impl<M, T: ?Sized + Deref> OsStrPiece<ViaBytes<Derefs<M>>> for T
+where
+    T::Target: OsStrPiece<ViaBytes<M>>,
+{
+    #[inline]
+    fn as_os_str(&self) -> &OsStr {
+        (**self).as_os_str()
+    }
+}
+
+#[inline]
+pub fn as_os_str<M, T: ?Sized + OsStrPiece<M>>(value: &T) -> &OsStr {
+    value.as_os_str()
+}
+
+#[cfg(windows)]
+pub struct ViaWide;
+
+/// Something that can be pushed into an `OsString`, which unlike an
+/// [`OsStrPiece`] may be converted first, like a wide string on Windows.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be used as an `OsStr` piece",
+    label = "expected something that implements `AsRef<OsStr>`"
+)]
+pub trait OsStringPiece<M> {
+    fn to_os_str(&self) -> Cow<'_, OsStr>;
+}
+
+impl<T: ?Sized + OsStrPiece<ViaAsRef>> OsStringPiece<ViaAsRef> for T {
+    #[inline]
+    fn to_os_str(&self) -> Cow<'_, OsStr> {
+        Cow::Borrowed(self.as_os_str())
+    }
+}
+
+impl<T: ?Sized + OsStrPiece<ViaCStr>> OsStringPiece<ViaCStr> for T {
+    #[inline]
+    fn to_os_str(&self) -> Cow<'_, OsStr> {
+        Cow::Borrowed(self.as_os_str())
+    }
+}
+
+#[cfg(unix)]
+impl<M, T: ?Sized + OsStrPiece<ViaBytes<M>>> OsStringPiece<ViaBytes<M>> for T {
+    #[inline]
+    fn to_os_str(&self) -> Cow<'_, OsStr> {
+        Cow::Borrowed(self.as_os_str())
+    }
+}
+
+#[cfg(windows)]
+impl<T: ?Sized + AsRef<[u16]>> OsStringPiece<ViaWide> for T {
+    #[inline]
+    fn to_os_str(&self) -> Cow<'_, OsStr> {
+        Cow::Owned(std::os::windows::ffi::OsStringExt::from_wide(self.as_ref()))
+    }
+}
+
+#[inline]
+pub fn to_os_str<M, T: ?Sized + OsStringPiece<M>>(value: &T) -> Cow<'_, OsStr> {
+    value.to_os_str()
+}
+
+/// Something that can be used as a path piece, where `M` tells whether it is
+/// raw bytes on Unix.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be used as a path piece",
+    label = "expected something that implements `AsRef<Path>`"
+)]
+pub trait PathPiece<M> {
+    fn as_path(&self) -> &Path;
+}
+
+impl<T: ?Sized + AsRef<Path>> PathPiece<ViaAsRef> for T {
+    #[inline]
+    fn as_path(&self) -> &Path {
+        self.as_ref()
+    }
+}
+
+#[cfg(unix)]
+impl<M, T: ?Sized + OsStrPiece<ViaBytes<M>>> PathPiece<ViaBytes<M>> for T {
+    #[inline]
+    fn as_path(&self) -> &Path {
+        Path::new(self.as_os_str())
+    }
+}
+
+#[inline]
+pub fn as_path<M, T: ?Sized + PathPiece<M>>(value: &T) -> &Path {
+    value.as_path()
+}
+
+/// An `OsStr` or a `Path`, whose bytes are taken for a slice on Unix.
+#[cfg(unix)]
+pub trait OsStrBytes {
+    fn os_str_bytes(&self) -> &[u8];
+}
+
+#[cfg(unix)]
+impl OsStrBytes for OsStr {
+    #[inline]
+    fn os_str_bytes(&self) -> &[u8] {
+        std::os::unix::ffi::OsStrExt::as_bytes(self)
+    }
+}
+
+#[cfg(unix)]
+impl OsStrBytes for Path {
+    #[inline]
+    fn os_str_bytes(&self) -> &[u8] {
+        self.as_os_str().os_str_bytes()
+    }
+}
+
+/// Something that can be used as a slice piece, where `M` tells whether it is
+/// a C string, whose bytes are taken without the NUL, or an `OsStr` on Unix.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be used as a slice piece",
+    label = "expected something that implements `AsRef<[{E}]>`"
+)]
+pub trait SlicePiece<E, M> {
+    fn as_slice(&self) -> &[E];
+}
+
+impl<E, T: ?Sized + AsRef<[E]>> SlicePiece<E, ViaAsRef> for T {
+    #[inline]
+    fn as_slice(&self) -> &[E] {
+        self.as_ref()
+    }
+}
+
+impl<T: ?Sized + Deref<Target = CStr>> SlicePiece<u8, ViaCStr> for T {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self.to_bytes()
+    }
+}
+
+#[cfg(unix)]
+impl<T: ?Sized + Deref<Target: OsStrBytes>> SlicePiece<u8, ViaOsStr> for T {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self.os_str_bytes()
+    }
+}
+
+#[inline]
+pub fn as_slice<E, M, T: ?Sized + SlicePiece<E, M>>(value: &T) -> &[E] {
+    value.as_slice()
+}
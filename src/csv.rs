@@ -0,0 +1,34 @@
+#[inline]
+fn needs_quoting(field: &str, sep: &str) -> bool {
+    field.bytes().any(|b| matches!(b, b'"' | b'\r' | b'\n'))
+        || (!sep.is_empty() && field.contains(sep))
+}
+
+/// The length of `field` after being quoted if needed.
+pub fn csv_field_len(field: &str, sep: &str) -> usize {
+    if needs_quoting(field, sep) {
+        field.len() + 2 + field.bytes().filter(|&b| b == b'"').count()
+    } else {
+        field.len()
+    }
+}
+
+/// Write `field`, enclosed in double quotes with its double quotes doubled
+/// if needed.
+pub fn emit_csv_field<F: FnMut(&str)>(field: &str, sep: &str, mut push_str: F) {
+    if !needs_quoting(field, sep) {
+        push_str(field);
+        return;
+    }
+
+    push_str("\"");
+    let mut parts = field.split('"');
+    if let Some(first) = parts.next() {
+        push_str(first);
+    }
+    for part in parts {
+        push_str("\"\"");
+        push_str(part);
+    }
+    push_str("\"");
+}
@@ -0,0 +1,83 @@
+/// The state of [`dedent_cat`](crate::dedent_cat), which measures the common
+/// indentation of the pieces in a first pass and strips it in a second one.
+pub struct Dedent {
+    /// The common indentation so far, `None` until a non-blank line is met.
+    margin: Option<usize>,
+    /// The number of non-blank lines.
+    lines: usize,
+    /// Whether the current line has been all spaces and tabs so far.
+    in_indent: bool,
+    /// The number of leading spaces and tabs of the current line so far.
+    indent: usize,
+}
+
+impl Default for Dedent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dedent {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            margin: None,
+            lines: 0,
+            in_indent: true,
+            indent: 0,
+        }
+    }
+
+    pub fn measure(&mut self, s: &str) {
+        for &b in s.as_bytes() {
+            if self.in_indent {
+                match b {
+                    b' ' | b'\t' => self.indent += 1,
+                    b'\n' => self.indent = 0,
+                    b'\r' => {}
+                    _ => {
+                        self.margin = Some(self.margin.map_or(self.indent, |m| m.min(self.indent)));
+                        self.lines += 1;
+                        self.in_indent = false;
+                    }
+                }
+            } else if b == b'\n' {
+                self.in_indent = true;
+                self.indent = 0;
+            }
+        }
+    }
+
+    /// Finish measuring and return the number of bytes that will be removed
+    /// from the non-blank lines.
+    pub fn start_pushing(&mut self) -> usize {
+        self.in_indent = true;
+        self.indent = 0;
+        self.margin.unwrap_or(0) * self.lines
+    }
+
+    pub fn push<F: FnMut(&str)>(&mut self, s: &str, mut push_str: F) {
+        let margin = self.margin.unwrap_or(0);
+        let mut run_start = 0;
+        for (i, &b) in s.as_bytes().iter().enumerate() {
+            if self.in_indent {
+                if (b == b' ' || b == b'\t') && self.indent < margin {
+                    self.indent += 1;
+                    if run_start < i {
+                        push_str(&s[run_start..i]);
+                    }
+                    run_start = i + 1;
+                    continue;
+                }
+                self.in_indent = false;
+            }
+            if b == b'\n' {
+                self.in_indent = true;
+                self.indent = 0;
+            }
+        }
+        if run_start < s.len() {
+            push_str(&s[run_start..]);
+        }
+    }
+}
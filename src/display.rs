@@ -0,0 +1,36 @@
+use std::fmt::Display;
+
+use crate::len::add_len;
+use crate::piece::{write_display, Displayed, Piece};
+
+/// Concatenate values only known at runtime through their `Display`
+/// implementations.
+///
+/// Every value is formatted once to be measured and once more to be written,
+/// so the result is allocated exactly once, like a [`@display`](crate#displayed-elements)
+/// element of the macros.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::concat_display;
+/// use std::fmt::Display;
+/// use std::net::Ipv4Addr;
+///
+/// let port = 8080;
+/// let pieces: Vec<&dyn Display> = vec![&Ipv4Addr::LOCALHOST, &':', &port];
+/// let s = concat_display(&pieces);
+/// assert_eq!(s, "127.0.0.1:8080");
+/// assert_eq!(s.capacity(), s.len());
+/// ```
+pub fn concat_display(pieces: &[&dyn Display]) -> String {
+    let len = pieces
+        .iter()
+        .map(|&piece| Displayed::new(piece).len())
+        .fold(0, add_len);
+    let mut s = String::with_capacity(len);
+    for &piece in pieces {
+        write_display(piece, |part| s.push_str(part));
+    }
+    s
+}
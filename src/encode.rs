@@ -0,0 +1,110 @@
+use crate::piece::Piece;
+
+/// Encode `value` into chunks of at most 64 bytes, with `encode` writing the
+/// encoded form of one `step`-byte group, or a shorter last group, at the
+/// start of the given slice and returning its length.
+#[inline]
+fn emit_encoded<E, F>(value: &[u8], step: usize, encode: E, mut push_str: F)
+where
+    E: Fn(&[u8], &mut [u8]) -> usize,
+    F: FnMut(&str),
+{
+    let mut buf = [0; 64];
+    let mut filled = 0;
+    for group in value.chunks(step) {
+        if filled + 4 > buf.len() {
+            // Both encodings are ASCII.
+            push_str(std::str::from_utf8(&buf[..filled]).unwrap());
+            filled = 0;
+        }
+        filled += encode(group, &mut buf[filled..]);
+    }
+    if filled > 0 {
+        push_str(std::str::from_utf8(&buf[..filled]).unwrap());
+    }
+}
+
+/// Bytes encoded as lowercase hexadecimal.
+#[cfg(feature = "hex")]
+pub struct Hex<'a> {
+    value: &'a [u8],
+}
+
+#[cfg(feature = "hex")]
+impl<'a> Hex<'a> {
+    #[inline]
+    pub fn new(value: &'a [u8]) -> Self {
+        Self { value }
+    }
+}
+
+#[cfg(feature = "hex")]
+impl Piece for Hex<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        crate::len::mul_len(self.value.len(), 2)
+    }
+
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        emit_encoded(
+            self.value,
+            1,
+            |group, out| {
+                out[0] = DIGITS[(group[0] >> 4) as usize];
+                out[1] = DIGITS[(group[0] & 0xf) as usize];
+                2
+            },
+            push_str,
+        );
+    }
+}
+
+/// Bytes encoded as standard Base64 with padding, as per
+/// [RFC 4648](https://www.rfc-editor.org/rfc/rfc4648#section-4).
+#[cfg(feature = "base64")]
+pub struct Base64<'a> {
+    value: &'a [u8],
+}
+
+#[cfg(feature = "base64")]
+impl<'a> Base64<'a> {
+    #[inline]
+    pub fn new(value: &'a [u8]) -> Self {
+        Self { value }
+    }
+}
+
+#[cfg(feature = "base64")]
+impl Piece for Base64<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        crate::len::mul_len(self.value.len().div_ceil(3), 4)
+    }
+
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        emit_encoded(
+            self.value,
+            3,
+            |group, out| {
+                let b = [
+                    group[0],
+                    group.get(1).copied().unwrap_or(0),
+                    group.get(2).copied().unwrap_or(0),
+                ];
+                let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+                for (i, out) in out[..4].iter_mut().enumerate() {
+                    *out = if i <= group.len() {
+                        ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize]
+                    } else {
+                        b'='
+                    };
+                }
+                4
+            },
+            push_str,
+        );
+    }
+}
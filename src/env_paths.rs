@@ -0,0 +1,27 @@
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+
+/// The separator of the entries of `PATH` and the like.
+#[cfg(not(windows))]
+pub const SEPARATOR: &str = ":";
+#[cfg(windows)]
+pub const SEPARATOR: &str = ";";
+
+#[inline]
+pub fn contains_separator(entry: &OsStr) -> bool {
+    entry.as_encoded_bytes().contains(&SEPARATOR.as_bytes()[0])
+}
+
+/// The error of [`env_paths_cat`](crate::env_paths_cat) when an entry contains
+/// the separator, in which case the target is left untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinPathsError;
+
+impl fmt::Display for JoinPathsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "path entry contains separator `{SEPARATOR}`")
+    }
+}
+
+impl Error for JoinPathsError {}
@@ -0,0 +1,181 @@
+use crate::piece::Piece;
+
+/// Write `s` in runs of bytes that need no escaping, and the escape sequences
+/// of the bytes that do.
+///
+/// `escape` must only return `Some` for ASCII bytes.
+#[inline]
+fn emit_escaped<E, F>(s: &str, escape: E, mut push_str: F)
+where
+    E: Fn(u8) -> Option<&'static str>,
+    F: FnMut(&str),
+{
+    let mut run_start = 0;
+    for (i, &b) in s.as_bytes().iter().enumerate() {
+        if let Some(escaped) = escape(b) {
+            if run_start < i {
+                push_str(&s[run_start..i]);
+            }
+            push_str(escaped);
+            run_start = i + 1;
+        }
+    }
+    if run_start < s.len() {
+        push_str(&s[run_start..]);
+    }
+}
+
+#[inline]
+fn escaped_len<E>(s: &str, escape: E) -> usize
+where
+    E: Fn(u8) -> Option<&'static str>,
+{
+    s.len()
+        + s.bytes()
+            .filter_map(escape)
+            .map(|escaped| escaped.len() - 1)
+            .sum::<usize>()
+}
+
+const JSON_CONTROL: [&str; 0x20] = [
+    "\\u0000", "\\u0001", "\\u0002", "\\u0003", "\\u0004", "\\u0005", "\\u0006", "\\u0007", "\\b",
+    "\\t", "\\n", "\\u000b", "\\f", "\\r", "\\u000e", "\\u000f", "\\u0010", "\\u0011", "\\u0012",
+    "\\u0013", "\\u0014", "\\u0015", "\\u0016", "\\u0017", "\\u0018", "\\u0019", "\\u001a",
+    "\\u001b", "\\u001c", "\\u001d", "\\u001e", "\\u001f",
+];
+
+#[inline]
+fn json_escape(b: u8) -> Option<&'static str> {
+    match b {
+        b'"' => Some("\\\""),
+        b'\\' => Some("\\\\"),
+        0x00..=0x1f => Some(JSON_CONTROL[b as usize]),
+        _ => None,
+    }
+}
+
+/// A piece escaped as the content of a JSON string.
+pub struct JsonEscaped<'a> {
+    value: &'a str,
+    len: usize,
+}
+
+impl<'a> JsonEscaped<'a> {
+    #[inline]
+    pub fn new(value: &'a str) -> Self {
+        Self {
+            value,
+            len: escaped_len(value, json_escape),
+        }
+    }
+}
+
+impl Piece for JsonEscaped<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        emit_escaped(self.value, json_escape, push_str)
+    }
+}
+
+#[cfg(feature = "html")]
+#[inline]
+fn html_escape(b: u8) -> Option<&'static str> {
+    match b {
+        b'&' => Some("&amp;"),
+        b'<' => Some("&lt;"),
+        b'>' => Some("&gt;"),
+        b'"' => Some("&quot;"),
+        b'\'' => Some("&#39;"),
+        _ => None,
+    }
+}
+
+/// A piece escaped for HTML and XML.
+#[cfg(feature = "html")]
+pub struct HtmlEscaped<'a> {
+    value: &'a str,
+    len: usize,
+}
+
+#[cfg(feature = "html")]
+impl<'a> HtmlEscaped<'a> {
+    #[inline]
+    pub fn new(value: &'a str) -> Self {
+        Self {
+            value,
+            len: escaped_len(value, html_escape),
+        }
+    }
+}
+
+#[cfg(feature = "html")]
+impl Piece for HtmlEscaped<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        emit_escaped(self.value, html_escape, push_str)
+    }
+}
+
+/// Escape the meta characters of the `regex` crate, like `regex::escape`.
+#[inline]
+fn regex_escape(b: u8) -> Option<&'static str> {
+    match b {
+        b'\\' => Some("\\\\"),
+        b'.' => Some("\\."),
+        b'+' => Some("\\+"),
+        b'*' => Some("\\*"),
+        b'?' => Some("\\?"),
+        b'(' => Some("\\("),
+        b')' => Some("\\)"),
+        b'|' => Some("\\|"),
+        b'[' => Some("\\["),
+        b']' => Some("\\]"),
+        b'{' => Some("\\{"),
+        b'}' => Some("\\}"),
+        b'^' => Some("\\^"),
+        b'$' => Some("\\$"),
+        b'#' => Some("\\#"),
+        b'&' => Some("\\&"),
+        b'-' => Some("\\-"),
+        b'~' => Some("\\~"),
+        _ => None,
+    }
+}
+
+/// A piece escaped to match itself literally in a regular expression.
+pub struct RegexEscaped<'a> {
+    value: &'a str,
+    len: usize,
+}
+
+impl<'a> RegexEscaped<'a> {
+    #[inline]
+    pub fn new(value: &'a str) -> Self {
+        Self {
+            value,
+            len: escaped_len(value, regex_escape),
+        }
+    }
+}
+
+impl Piece for RegexEscaped<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        emit_escaped(self.value, regex_escape, push_str)
+    }
+}
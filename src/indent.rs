@@ -0,0 +1,34 @@
+/// Options of [`indent_cat`](crate::indent_cat).
+#[derive(Default)]
+pub struct IndentOptions<'a> {
+    /// Written at the start of every non-empty line.
+    pub indent: &'a str,
+    /// Reserved on top of the counted length, for pushing more later.
+    pub reserve_extra: usize,
+    /// Whether the buffer is shrunk to its length in the end.
+    pub fit: bool,
+}
+
+#[inline]
+pub fn count_newlines(s: &str) -> usize {
+    s.bytes().filter(|&b| b == b'\n').count()
+}
+
+/// Push `s` with `indent` written at the start of every non-empty line.
+///
+/// `at_line_start` carries whether the last pushed byte ended a line, across
+/// pieces.
+pub fn push_indented<F: FnMut(&str)>(
+    s: &str,
+    indent: &str,
+    at_line_start: &mut bool,
+    mut push_str: F,
+) {
+    for line in s.split_inclusive('\n') {
+        if *at_line_start && line != "\n" && line != "\r\n" {
+            push_str(indent);
+        }
+        push_str(line);
+        *at_line_start = line.ends_with('\n');
+    }
+}
@@ -0,0 +1,195 @@
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+/// A string of at most `N` bytes stored inline, as built by
+/// [`inline_str_cat`](crate::inline_str_cat) without allocating.
+///
+/// It dereferences to [`str`](str), and compares and hashes like one, so it
+/// can be looked up in maps keyed by `&str`.
+#[derive(Clone, Copy)]
+pub struct InlineString<const N: usize> {
+    buf: [u8; N],
+    /// Never more than `N`, and `buf[..len]` is always valid UTF-8.
+    len: usize,
+}
+
+impl<const N: usize> InlineString<N> {
+    /// An empty string.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// The string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: `buf[..len]` is valid UTF-8, since only whole strings are
+        // pushed after it, and it is only truncated at a char boundary.
+        unsafe { std::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+
+    /// The maximum number of bytes, which is `N`.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// The number of bytes that can still be pushed.
+    #[inline]
+    pub const fn remaining_capacity(&self) -> usize {
+        N - self.len
+    }
+
+    /// Append `s`, or fail without changing anything if it does not fit.
+    pub fn try_push_str(&mut self, s: &str) -> Result<(), CapacityError> {
+        if s.len() > self.remaining_capacity() {
+            return Err(CapacityError);
+        }
+        let end = self.len + s.len();
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+
+    /// Shorten the string to `new_len` bytes, which has no effect if it is
+    /// not longer than that.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` does not lie on a `char` boundary.
+    #[inline]
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len < self.len {
+            assert!(
+                self.as_str().is_char_boundary(new_len),
+                "new_len does not lie on a char boundary",
+            );
+            self.len = new_len;
+        }
+    }
+
+    /// Remove everything.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for InlineString<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for InlineString<N> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for InlineString<N> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> Borrow<str> for InlineString<N> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for InlineString<N> {
+    type Error = CapacityError;
+
+    #[inline]
+    fn try_from(s: &str) -> Result<Self, CapacityError> {
+        let mut inline = Self::new();
+        inline.try_push_str(s)?;
+        Ok(inline)
+    }
+}
+
+impl<const N: usize> PartialEq for InlineString<N> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for InlineString<N> {}
+
+impl<const N: usize> PartialEq<str> for InlineString<N> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for InlineString<N> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialOrd for InlineString<N> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for InlineString<N> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> Hash for InlineString<N> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl<const N: usize> fmt::Display for InlineString<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for InlineString<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// The error of [`inline_str_cat`](crate::inline_str_cat) when the pieces do
+/// not fit in an [`InlineString`], in which case the target is left
+/// untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError;
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the pieces exceed the capacity of the inline string")
+    }
+}
+
+impl Error for CapacityError {}
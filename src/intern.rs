@@ -0,0 +1,27 @@
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+
+/// An interner taking the strings built by
+/// [`intern_cat`](crate::intern_cat).
+pub trait Interner {
+    /// The handle returned for an interned string.
+    type Symbol;
+
+    /// Intern `s`, returning the same symbol for equal strings.
+    fn intern(&mut self, s: &str) -> Self::Symbol;
+}
+
+/// Every distinct string is leaked once, so the set only suits a bounded
+/// number of names living as long as the program.
+impl<S: BuildHasher> Interner for HashSet<&'static str, S> {
+    type Symbol = &'static str;
+
+    fn intern(&mut self, s: &str) -> &'static str {
+        if let Some(&interned) = self.get(s) {
+            return interned;
+        }
+        let interned: &'static str = Box::leak(s.into());
+        self.insert(interned);
+        interned
+    }
+}
@@ -0,0 +1,78 @@
+use std::ffi::OsStr;
+
+/// Options of the joining macros, filled in from the `key = value` items in
+/// front of the `;`.
+pub struct JoinOptions<'a, S: ?Sized> {
+    /// Pushed between every two pieces.
+    pub sep: &'a S,
+    /// Pushed before the last piece instead of `sep`.
+    pub last_sep: Option<&'a S>,
+    /// Whether empty pieces are left out along with their separators.
+    pub skip_empty: bool,
+    /// Pushed once before all pieces.
+    pub prefix: &'a S,
+    /// Pushed once after all pieces.
+    pub suffix: &'a S,
+    /// Pushed after every piece.
+    pub terminator: &'a S,
+    /// Reserved on top of the counted length, for pushing more later.
+    pub reserve_extra: usize,
+    /// Whether the buffer is shrunk to its length in the end.
+    pub fit: bool,
+}
+
+impl<'a, S: ?Sized> Default for JoinOptions<'a, S>
+where
+    &'a S: Default,
+{
+    fn default() -> Self {
+        Self {
+            sep: Default::default(),
+            last_sep: None,
+            skip_empty: false,
+            prefix: Default::default(),
+            suffix: Default::default(),
+            terminator: Default::default(),
+            reserve_extra: 0,
+            fit: false,
+        }
+    }
+}
+
+impl JoinOptions<'_, OsStr> {
+    /// The defaults of [`env_paths_cat`](crate::env_paths_cat).
+    pub fn env_paths() -> Self {
+        Self {
+            sep: OsStr::new(crate::env_paths::SEPARATOR),
+            ..Default::default()
+        }
+    }
+}
+
+impl JoinOptions<'_, str> {
+    /// The defaults of [`csv_cat`](crate::csv_cat).
+    pub fn csv() -> Self {
+        Self {
+            sep: ",",
+            suffix: "\r\n",
+            ..Default::default()
+        }
+    }
+
+    /// The defaults of [`lines_cat`](crate::lines_cat).
+    pub fn lines() -> Self {
+        Self {
+            terminator: "\n",
+            ..Default::default()
+        }
+    }
+
+    /// The defaults of [`query_cat`](crate::query_cat).
+    pub fn query() -> Self {
+        Self {
+            sep: "&",
+            skip_empty: true,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,70 @@
+/// Add up lengths, panicking if the sum overflows instead of reserving too
+/// little.
+#[inline]
+pub fn add_len(a: usize, b: usize) -> usize {
+    a.checked_add(b).unwrap_or_else(|| overflow())
+}
+
+/// Multiply a length by a count, panicking if the product overflows instead
+/// of reserving too little.
+#[inline]
+pub fn mul_len(len: usize, count: usize) -> usize {
+    len.checked_mul(count).unwrap_or_else(|| overflow())
+}
+
+#[cold]
+#[inline(never)]
+fn overflow() -> ! {
+    panic!("capacity overflow: the total length of the pieces exceeds `usize::MAX`")
+}
+
+#[cfg(feature = "debug-capacity")]
+thread_local! {
+    /// How many pieces have been written without being counted, which are
+    /// allowed to grow the buffer.
+    static UNCOUNTED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Note that a piece was written without being counted.
+#[inline]
+pub fn note_uncounted() {
+    #[cfg(feature = "debug-capacity")]
+    UNCOUNTED.with(|uncounted| uncounted.set(uncounted.get() + 1));
+}
+
+/// The capacity right after reserving, which is asserted not to have grown
+/// after pushing with the `debug-capacity` feature, to catch pieces that
+/// were counted short.
+pub struct ReservedCapacity {
+    #[cfg(feature = "debug-capacity")]
+    capacity: usize,
+    #[cfg(feature = "debug-capacity")]
+    uncounted: usize,
+}
+
+impl ReservedCapacity {
+    #[inline]
+    pub fn new(capacity: usize) -> Self {
+        #[cfg(not(feature = "debug-capacity"))]
+        let _ = capacity;
+        Self {
+            #[cfg(feature = "debug-capacity")]
+            capacity,
+            #[cfg(feature = "debug-capacity")]
+            uncounted: UNCOUNTED.with(|uncounted| uncounted.get()),
+        }
+    }
+
+    #[inline]
+    pub fn check(self, capacity: usize) {
+        #[cfg(feature = "debug-capacity")]
+        if UNCOUNTED.with(|uncounted| uncounted.get()) == self.uncounted {
+            debug_assert_eq!(
+                self.capacity, capacity,
+                "the buffer was reallocated after reserving the counted length",
+            );
+        }
+        #[cfg(not(feature = "debug-capacity"))]
+        let _ = capacity;
+    }
+}
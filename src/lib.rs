@@ -57,6 +57,37 @@
 //! assert_eq!(s, "Hello World!");
 //! ```
 //!
+//! A shared [`Arc<String>`](std::sync::Arc) or [`Rc<String>`](std::rc::Rc)
+//! can be appended to as well, which is cloned first only if it is not
+//! unique, like [`Arc::make_mut`](std::sync::Arc::make_mut).
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! use std::sync::Arc;
+//!
+//! let mut title = Arc::new("Untitled".to_owned());
+//! let shown = Arc::clone(&title);
+//! str_cat!(&mut title; " - ", "Editor");
+//! assert_eq!(*title, "Untitled - Editor");
+//! assert_eq!(*shown, "Untitled");
+//! ```
+//!
+//! Likewise, a [`Cow<str>`](std::borrow::Cow) stays borrowed unless
+//! something non-empty is appended, in which case it is copied once with room
+//! for all the pieces.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! use std::borrow::Cow;
+//!
+//! let suffix = "";
+//! let mut name = Cow::Borrowed("config");
+//! str_cat!(&mut name; suffix);
+//! assert!(matches!(name, Cow::Borrowed("config")));
+//! str_cat!(&mut name; ".", "toml");
+//! assert_eq!(name, "config.toml");
+//! ```
+//!
 //! ## Reuse existing allocation
 //! ```
 //! # use str_cat::str_cat;
@@ -80,6 +111,103 @@
 //! assert!(s.capacity() >= 16);
 //! ```
 //!
+//! ## Extra capacity
+//! `reserve_extra = n` after the target, if any, reserves `n` more bytes
+//! along with the counted length, for a buffer to be extended later.
+//! ```
+//! # use str_cat::str_cat;
+//! let mut s = String::new();
+//! str_cat!(&mut s, reserve_extra = 64; "foo", "bar");
+//! assert_eq!(s, "foobar");
+//! assert!(s.capacity() >= 6 + 64);
+//!
+//! let s = str_cat!(reserve_extra = 64; "foo", "bar");
+//! assert!(s.capacity() >= 6 + 64);
+//! ```
+//!
+//! It goes along with the options of the joining and indenting macros too.
+//! ```
+//! # use str_cat::{indent_cat, lines_cat, str_join};
+//! let s = str_join!(sep = ", ", reserve_extra = 64; "foo", "bar");
+//! assert!(s.capacity() >= 8 + 64);
+//!
+//! let s = lines_cat!(reserve_extra = 64; "foo", "bar");
+//! assert!(s.capacity() >= 8 + 64);
+//!
+//! let s = indent_cat!(indent = "  ", reserve_extra = 64; "foo\n", "bar");
+//! assert!(s.capacity() >= 10 + 64);
+//! ```
+//!
+//! ## Shrinking to fit
+//! A `fit = true` option shrinks the buffer to its length in the end, for a
+//! string to be kept around for long.
+//! ```
+//! # use str_cat::{str_cat, str_join};
+//! let s = str_cat!(String::with_capacity(64), fit = true; "foo", "bar");
+//! assert_eq!(s, "foobar");
+//! assert_eq!(s.capacity(), 6);
+//!
+//! let s = str_cat!(fit = true; "foo", "bar");
+//! assert_eq!(s.capacity(), 6);
+//!
+//! let s = str_join!(sep = ", ", fit = true; "foo", "bar");
+//! assert_eq!(s.capacity(), 8);
+//!
+//! // A target can still be named `fit`.
+//! let fit = String::from("x");
+//! assert_eq!(str_cat!(fit; "y"), "xy");
+//! ```
+//!
+//! ## Boxed output
+//! A `boxed = true` option without a target returns the result as an exactly
+//! sized box, such as a [`Box<str>`](Box) for [`str_cat`](str_cat) or a
+//! `Box<[T]>` for [`vec_cat`](vec_cat), for an immutable value to be stored or
+//! sent.
+//! ```
+//! # use str_cat::{str_cat, vec_cat};
+//! let s: Box<str> = str_cat!(boxed = true; "foo", "bar");
+//! assert_eq!(&*s, "foobar");
+//!
+//! let payload: Box<[u8]> = vec_cat!(boxed = true; b"\x01\x00", b"ping");
+//! assert_eq!(&*payload, b"\x01\x00ping");
+//!
+//! // A target can still be named `boxed`.
+//! let boxed = vec![1u8];
+//! assert_eq!(vec_cat!(boxed; b"y"), [1, b'y']);
+//! ```
+//!
+//! ## Counting the appended bytes
+//! A `count = true` option makes the macro return the number of bytes it
+//! appended instead of the target, such as for backfilling a length prefix.
+//! It works with every macro that returns its target, while the ones that
+//! return a `Result`, such as [`strict_path_cat`](strict_path_cat), reject it.
+//! `count = false` is the same as leaving it out. A target that ends up
+//! shorter than it started, like a path replaced by an absolute component,
+//! counts as having appended nothing.
+//! ```
+//! # use str_cat::{path_cat, str_cat};
+//! # use std::path::PathBuf;
+//! let mut frame = String::from("0000");
+//! let len = str_cat!(&mut frame, count = true; "HELO ", "example.com");
+//! frame.replace_range(..4, &format!("{len:04}"));
+//! assert_eq!(frame, "0016HELO example.com");
+//!
+//! let mut p = PathBuf::from("/srv/www/data");
+//! assert_eq!(path_cat!(&mut p, count = true; "/b"), 0);
+//! path_cat!(&mut p, count = false; "c");
+//! assert_eq!(p, PathBuf::from("/b/c"));
+//!
+//! // A target can still be named `count`.
+//! let count = String::from("x");
+//! assert_eq!(str_cat!(count; "y"), "xy");
+//! ```
+//!
+//! ## Checking the capacity
+//! With the `debug-capacity` feature, every macro asserts with
+//! [`debug_assert!`](debug_assert) that the buffer was not reallocated after
+//! reserving the counted length, which catches pieces counted short while
+//! testing.
+//!
 //! ## Argument types
 //! Works with any expressions that can dereference to [`str`](str) when
 //! evaluated. Although it should be more simple and efficient to use
@@ -112,6 +240,352 @@
 //! assert_eq!(world, "World!"); // not moved, still valid
 //! ```
 //!
+//! ## Special elements
+//! Elements starting with `@`, like `@when(..)` and `@display(..)` below, are
+//! handled by the macros. Every other element is a plain piece, including a
+//! call to a function of your own that shares a name with one of them.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! fn repeat(s: &str, n: u8) -> String {
+//!     s.repeat(n.into())
+//! }
+//! fn chars(s: &str) -> &str {
+//!     s
+//! }
+//!
+//! let s = str_cat!(repeat("ab", 2u8), chars("!"), @repeat("-", 2));
+//! assert_eq!(s, "abab!--");
+//! ```
+//!
+//! ## Conditional elements
+//! An element written as `@when(cond, piece)` or `@when(let pattern = expr,
+//! piece)` is included only when the condition holds or the pattern matches.
+//! The condition is evaluated exactly once and the element is counted into the
+//! capacity only when taken. Patterns match against a reference to the
+//! scrutinee, so nothing is moved out of it.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let where_clause = Some(" WHERE id = 1".to_owned());
+//! let limit = None::<u32>;
+//! let distinct = false;
+//!
+//! let s = str_cat!(
+//!     "SELECT",
+//!     @when(distinct, " DISTINCT"),
+//!     " * FROM t",
+//!     @when(let Some(w) = where_clause, w),
+//!     @when(let Some(n) = limit, str_cat!(" LIMIT ", n.to_string())),
+//!     ";",
+//! );
+//! assert_eq!(s, "SELECT * FROM t WHERE id = 1;");
+//! assert!(where_clause.is_some()); // not moved, still valid
+//! ```
+//!
+//! Plain `if` expressions are pieces like any other expression.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let flag = true;
+//! let s = str_cat!(if flag { "a" } else { "b" }, "c");
+//! assert_eq!(s, "ac");
+//! ```
+//!
+//! ## Repeated elements
+//! An element written as `@repeat(piece, count)` pushes `piece` `count` times,
+//! where `count` is a `usize` known only at runtime. The piece is evaluated
+//! once and `piece.len() * count` is counted into the capacity.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let depth = 3;
+//! let s = str_cat!(@repeat("  ", depth), "line", @repeat("!", 0));
+//! assert_eq!(s, "      line");
+//! ```
+//!
+//! The lengths are added up with overflow checks, so a total length that
+//! doesn't fit in a `usize` panics before anything is reserved or pushed.
+//!
+//! ```should_panic
+//! # use str_cat::str_cat;
+//! let s = str_cat!(@repeat("!", usize::MAX), "?"); // panics with "capacity overflow"
+//! ```
+//!
+//! ## Spread elements
+//! An element written as `@all(pieces)` pushes every item of `pieces`, which
+//! is borrowed like other elements and can be anything whose reference can be
+//! iterated over, such as a slice, an array or a `Vec`. `pieces` is evaluated
+//! once and iterated twice, first to count all the items into the capacity
+//! and then to push them. In the joining macros, every item is a piece of its
+//! own.
+//!
+//! ```
+//! # use str_cat::{str_cat, str_join};
+//! let parts = vec!["usr".to_owned(), "local".to_owned()];
+//! let s = str_cat!("/", @all(parts), "/bin");
+//! assert_eq!(s, "/usrlocal/bin");
+//!
+//! let s = str_join!(sep = "/"; "", @all(parts[..]), "bin");
+//! assert_eq!(s, "/usr/local/bin");
+//! ```
+//!
+//! Likewise, `@iter(iter)` pushes every item of an iterator, which must be
+//! [`Clone`](Clone) since a clone of it is iterated over to count the items.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let words = "a quick fox";
+//! let s = str_cat!("#", @iter(words.split(' ').filter(|w| w.len() > 1)));
+//! assert_eq!(s, "#quickfox");
+//! ```
+//!
+//! ## Fallible elements
+//! Elements can use `?` in functions returning a [`Result`](Result) or an
+//! [`Option`](Option), so no separate fallible macro is needed. Every element
+//! is evaluated once, in order, before anything is pushed, so the first error
+//! is propagated without evaluating the rest, and leaves the target untouched.
+//! Otherwise the capacity is still reserved once.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! use std::str::{self, Utf8Error};
+//!
+//! fn greet(out: &mut String, name: &[u8], title: &[u8]) -> Result<(), Utf8Error> {
+//!     str_cat!(out; "Hello, ", str::from_utf8(title)?, " ", str::from_utf8(name)?, "!\n");
+//!     Ok(())
+//! }
+//!
+//! let mut s = String::new();
+//! greet(&mut s, b"Ferris", b"Dr.").unwrap();
+//! assert_eq!(s, "Hello, Dr. Ferris!\n");
+//! assert!(greet(&mut s, b"\xff", b"Mx.").is_err());
+//! assert_eq!(s, "Hello, Dr. Ferris!\n");
+//! ```
+//!
+//! ## Displayed elements
+//! `@display(value)` writes anything that implements
+//! [`Display`](std::fmt::Display) without an intermediate
+//! [`String`](String). It is formatted twice, once to be counted into the
+//! capacity and once to be pushed, so it should be cheap to format and give
+//! the same output both times.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let n = 42;
+//! let s = str_cat!("You have ", @display(n), " new messages");
+//! assert_eq!(s, "You have 42 new messages");
+//! ```
+//!
+//! A [`format_args!`](format_args) element is written while being formatted,
+//! only once, and is left out of the capacity reserved for the other
+//! elements, so it may grow the buffer once more. The exception is
+//! [`dedent_cat`](dedent_cat), which has to format it once more to measure
+//! its indentation.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let x = 7;
+//! let s = str_cat!("id-", format_args!("{x:04}"), ".txt");
+//! assert_eq!(s, "id-0007.txt");
+//! ```
+//!
+//! Likewise, `@dbg(value)` writes anything that implements
+//! [`Debug`](std::fmt::Debug) like `{:?}`, formatted only once outside of
+//! `dedent_cat`.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let path = std::path::Path::new("/tmp/a b");
+//! let flags = ["-r", "-f"];
+//! let s = str_cat!("rm ", @dbg(flags), " ", @dbg(path));
+//! assert_eq!(s, r#"rm ["-r", "-f"] "/tmp/a b""#);
+//! ```
+//!
+//! With the `fmt` feature, `cat_fmt!` offers the same with inline
+//! interpolation like [`format!`](format).
+//!
+#![cfg_attr(feature = "fmt", doc = "```")]
+#![cfg_attr(not(feature = "fmt"), doc = "```ignore")]
+//! use str_cat::cat_fmt;
+//!
+//! let n = 42;
+//! let s = cat_fmt!("You have {n} new messages");
+//! assert_eq!(s, "You have 42 new messages");
+//! ```
+//!
+//! ## Char elements
+//! `@chars(iter)` writes the `char`s of an iterator, such as a filtered or
+//! mapped [`Chars`](std::str::Chars), without collecting them first. The
+//! iterator must be [`Clone`](Clone), and a clone of it is consumed while
+//! being written, so only the lower bound of its size hint is counted into
+//! the capacity.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let title = "Hello, World!";
+//! let slug = title.chars().filter(|c| c.is_alphanumeric()).map(|c| c.to_ascii_lowercase());
+//! let s = str_cat!("/posts/", @chars(slug), "/", @chars(['é'; 2]));
+//! assert_eq!(s, "/posts/helloworld/éé");
+//! ```
+//!
+//! ## Padding
+//! `@pad(piece, [fill] align width)` pads `piece` with `fill` (a space by
+//! default) up to `width` `char`s, where `align` is one of `<`, `>` and `^`,
+//! just like the `{:fill align width}` of [`format!`](format). The padding is
+//! written directly into the reserved buffer. It is only supported by
+//! [`str_cat`](str_cat) and [`str_join`](str_join).
+//!
+//! With the `unicode-width` feature, `@pad_cols` takes the same arguments but
+//! measures the width in terminal columns instead, so that wide characters
+//! such as CJK and emoji are aligned correctly.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let id = "42";
+//! let width = 6;
+//! let s = str_cat!("|", @pad(id, '0' > 4), "|", @pad("ok", ^ width), "|", @pad("名前", 4), "|");
+//! assert_eq!(s, "|0042|  ok  |名前  |");
+//! assert_eq!(s, format!("|{:0>4}|{:^width$}|{:4}|", id, "ok", "名前"));
+//! ```
+//!
+#![cfg_attr(feature = "unicode-width", doc = "```")]
+#![cfg_attr(not(feature = "unicode-width"), doc = "```ignore")]
+//! # use str_cat::str_cat;
+//! let s = str_cat!("|", @pad_cols("名前", 6), "|", @pad_cols("id", 6), "|");
+//! assert_eq!(s, "|名前  |id    |");
+//! ```
+//!
+//! With the `ansi` feature, `@pad_ansi` leaves SGR escape sequences, which
+//! color and style terminal output, out of the width, and so does
+//! `@pad_ansi_cols` along with the `unicode-width` feature.
+//!
+#![cfg_attr(feature = "ansi", doc = "```")]
+#![cfg_attr(not(feature = "ansi"), doc = "```ignore")]
+//! # use str_cat::str_cat;
+//! let status = "\x1b[32mok\x1b[0m";
+//! let s = str_cat!("|", @pad_ansi(status, 6), "|", @pad_ansi("\x1b[1;31mfail\x1b[m", > 6), "|");
+//! assert_eq!(s, "|\x1b[32mok\x1b[0m    |  \x1b[1;31mfail\x1b[m|");
+//! ```
+//!
+//! ## Trimming
+//! `@trim(piece)`, `@trim_start(piece)` and `@trim_end(piece)` leave out the
+//! leading and trailing whitespace of `piece`, which is only counted into the
+//! capacity after being trimmed.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let line = "  user@example.com \n";
+//! let s = str_cat!("<", @trim(line), ">", @trim_end("ok\r\n"));
+//! assert_eq!(s, "<user@example.com>ok");
+//! ```
+//!
+//! ## Regex escaping
+//! `@re_esc(piece)` escapes the meta characters of `piece` with backslashes
+//! while copying it, just like `regex::escape` of the `regex` crate, so that
+//! it matches itself literally in a dynamic pattern.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let prefix = "v1.2";
+//! let name = "a+b (copy)";
+//! let s = str_cat!("^", @re_esc(prefix), ".*", @re_esc(name), "$");
+//! assert_eq!(s, r"^v1\.2.*a\+b \(copy\)$");
+//! ```
+//!
+//! ## Case mapping
+//! `@upper(piece)` and `@lower(piece)` map `piece` to upper or lower case while
+//! copying it, without a temporary `String`. ASCII pieces take a fast path,
+//! while other characters are mapped one by one like
+//! [`char::to_uppercase`](char::to_uppercase).
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let name = "Content-Type";
+//! let s = str_cat!(@lower(name), ": ", @upper("straße"));
+//! assert_eq!(s, "content-type: STRASSE");
+//! ```
+//!
+//! ## Replacing
+//! `@replace(piece, from, to)` replaces every match of the string `from` in
+//! `piece` with `to` while copying it, like [`str::replace`](str::replace)
+//! but without a temporary `String` for every piece.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! let msg = "line 1\nline 2";
+//! let s = str_cat!("msg=\"", @replace(msg, "\n", "\\n"), "\"");
+//! assert_eq!(s, r#"msg="line 1\nline 2""#);
+//! ```
+//!
+//! ## Percent-encoding
+//! With the `percent-encoding` feature, `@enc(piece)` percent-encodes `piece`
+//! while copying it, keeping only the unreserved characters of RFC 3986
+//! (`percent::COMPONENT`). Another `AsciiSet` can be selected with
+//! `@enc(piece, set)`.
+//!
+#![cfg_attr(feature = "percent-encoding", doc = "```")]
+#![cfg_attr(not(feature = "percent-encoding"), doc = "```ignore")]
+//! # use str_cat::str_cat;
+//! use str_cat::percent::CONTROLS;
+//!
+//! let q = "rust & café";
+//! let s = str_cat!("https://example.com/search?q=", @enc(q), "#", @enc("a b", CONTROLS));
+//! assert_eq!(s, "https://example.com/search?q=rust%20%26%20caf%C3%A9#a b");
+//! ```
+//!
+//! ## Binary-to-text encoding
+//! With the `hex` feature, `@hex(bytes)` writes anything that implements
+//! [`AsRef<[u8]>`](AsRef) as lowercase hexadecimal, and with the `base64`
+//! feature, `@b64(bytes)` writes it as standard Base64 with padding. The
+//! encoded length is counted exactly.
+//!
+#![cfg_attr(all(feature = "hex", feature = "base64"), doc = "```")]
+#![cfg_attr(not(all(feature = "hex", feature = "base64")), doc = "```ignore")]
+//! # use str_cat::str_cat;
+//! let digest = [0xde, 0xad, 0xbe, 0xef];
+//! let s = str_cat!("sha256=", @hex(digest));
+//! assert_eq!(s, "sha256=deadbeef");
+//!
+//! let png = b"\x89PNG";
+//! let s = str_cat!("data:image/png;base64,", @b64(png));
+//! assert_eq!(s, "data:image/png;base64,iVBORw==");
+//! ```
+//!
+//! The markers start with `@`, so `hex` and `b64` are free for functions of
+//! your own, with or without the features.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! fn hex(n: u32) -> String {
+//!     format!("{n:x}")
+//! }
+//! assert_eq!(str_cat!("0x", hex(255)), "0xff");
+//! ```
+//!
+//! ## Paths
+//! `@lossy(path)` writes anything that implements [`AsRef<OsStr>`](AsRef) like
+//! [`to_string_lossy`](std::ffi::OsStr::to_string_lossy), but without
+//! allocating, while `@strict(path)` requires it to be valid UTF-8 and
+//! propagates the [`Utf8Error`](std::str::Utf8Error) with `?` otherwise.
+//! Unpaired surrogates on Windows are replaced by one U+FFFD for each of
+//! their bytes in `@lossy`, rather than one for each of them.
+//!
+//! ```
+//! # use str_cat::str_cat;
+//! use std::path::Path;
+//!
+//! # fn main() -> Result<(), std::str::Utf8Error> {
+//! let path = Path::new("/tmp/report.txt");
+//! let s = str_cat!("saved to ", @lossy(path));
+//! assert_eq!(s, "saved to /tmp/report.txt");
+//!
+//! let s = str_cat!("saved to ", @strict(path));
+//! assert_eq!(s, "saved to /tmp/report.txt");
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Variants
 //! There are also variants for [`PathBuf`](std::path::PathBuf),
 //! [`OsString`](std::ffi::OsString) and [`Vec`](Vec).
@@ -131,6 +605,58 @@
 //! assert_eq!(s, OsStr::new("Hello World!"));
 //! ```
 
+mod builder;
+mod case;
+mod cat;
+mod chain;
+mod coerce;
+mod csv;
+mod dedent;
+mod display;
+#[cfg(any(feature = "hex", feature = "base64"))]
+mod encode;
+mod env_paths;
+mod escape;
+mod indent;
+mod inline;
+mod intern;
+mod join;
+mod len;
+mod normalize_path;
+mod owned;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "percent-encoding")]
+mod percent_encoded;
+mod piece;
+mod pool;
+mod replace;
+mod scratch;
+mod shell;
+mod sql;
+mod strict_path;
+mod target;
+#[cfg(feature = "percent-encoding")]
+mod url;
+mod utf16;
+
+pub use builder::StrCat;
+pub use cat::CatTarget;
+pub use chain::Chain;
+pub use display::concat_display;
+pub use env_paths::JoinPathsError;
+pub use inline::{CapacityError, InlineString};
+pub use intern::Interner;
+pub use owned::concat_owned;
+#[cfg(feature = "rayon")]
+pub use par::par_concat;
+pub use pool::{PooledString, StringPool};
+pub use scratch::Scratch;
+pub use strict_path::StrictPathError;
+
+#[cfg(feature = "percent-encoding")]
+pub mod percent;
+
 /// Concatenate strings for a [`String`](String).
 ///
 /// It requires all elements to be able to dereference to [`str`](str) (impl [`Deref<Target = str>`](std::ops::Deref)).
@@ -147,184 +673,2329 @@
 /// s.clear();
 /// str_cat!(&mut s; "foo", "bar");
 /// assert_eq!(s, "foobar");
+///
+/// // No elements at all, as expanded from another macro.
+/// macro_rules! greet {
+///     ($($name:expr),*) => { str_cat!("Hello", $(" ", $name),*) };
+/// }
+/// assert_eq!(greet!(), "Hello");
+/// assert_eq!(str_cat!(), "");
+/// ```
+///
+/// Elements are expanded a few at a time, so a single call can take well
+/// over a hundred of them under the default recursion limit.
+///
+/// ```
+/// use str_cat::str_cat;
+///
+/// let x = "x";
+/// let s = str_cat!(
+///     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// #     "x", x, "x", x, "x", x, "x", x, "x", x, "x", x,
+/// );
+/// assert_eq!(s, "x".repeat(120));
 /// ```
 #[macro_export]
 macro_rules! str_cat {
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*;) => {
-        $input.reserve($additional);
-        $($input.push_str($values_coerced);)*
-    };
-
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*; $head:expr, $($tail:expr,)*) => {
-        match &$head {
-            value => {
-                let value_coerced: &str = &*value;
-                $additional += value_coerced.len();
-                $crate::str_cat!(@stack $input, $additional; $($values_coerced)* value_coerced; $($tail,)*);
-            }
-        }
-    };
-
-    ($input:expr; $($el:expr),+ $(,)?) => {{
-        #[allow(unused_mut)]
-        let mut input = $input;
-        let mut additional = 0;
-        $crate::str_cat!(@stack input, additional; ; $($el,)*);
-        input
-    }};
-
-    ($($el:expr),+ $(,)?) => {
-        $crate::str_cat!(::std::string::String::new(); $($el,)*)
+    ($($tt:tt)*) => {
+        $crate::__cat!(str cat; $($tt)*)
     };
 }
 
-/// Concatenate paths for a [`PathBuf`](std::path::PathBuf).
+/// Concatenate pieces for the type inferred from the context, which
+/// implements [`CatTarget`](CatTarget).
 ///
-/// It requires all elements to implement [`AsRef<Path>`](AsRef).
+/// The same call can build a [`String`](String), an
+/// [`OsString`](std::ffi::OsString), a [`PathBuf`](std::path::PathBuf) or a
+/// [`Vec`](Vec), which suits generic code. Every element is an expression that
+/// is borrowed and converted with [`AsRef`](AsRef) to the piece type of the
+/// target, without the special elements of the dedicated macros. Like those,
+/// every element is evaluated once and the capacity is reserved once.
 ///
 /// # Example
 ///
 /// ```
-/// use str_cat::path_cat;
-/// use std::ffi::OsStr;
-/// use std::path::{Path, PathBuf};
+/// use std::ffi::OsString;
+/// use std::path::PathBuf;
+/// use str_cat::cat;
 ///
-/// let mut s = path_cat!("Hello", "space".to_owned(), Path::new("World"), OsStr::new("bang"));
-/// assert_eq!(s, ["Hello", "space", "World", "bang"].iter().collect::<PathBuf>());
+/// let name = "str-cat".to_owned();
+/// let s: String = cat!("crates/", name);
+/// assert_eq!(s, "crates/str-cat");
 ///
-/// // Reusing allocation.
-/// s.clear();
-/// path_cat!(&mut s; "foo", "bar");
-/// assert_eq!(s, ["foo", "bar"].iter().collect::<PathBuf>());
+/// let s: OsString = cat!("crates/", name);
+/// assert_eq!(s, "crates/str-cat");
+///
+/// // Paths are built from components like `path_cat!`.
+/// let path: PathBuf = cat!("crates", name, "Cargo.toml");
+/// assert_eq!(path, PathBuf::from("crates/str-cat/Cargo.toml"));
+///
+/// let bytes: Vec<u8> = cat!(b"crates/", name);
+/// assert_eq!(bytes, b"crates/str-cat");
 /// ```
 #[macro_export]
-macro_rules! path_cat {
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*;) => {
-        $input.reserve($additional);
-        $($input.push($values_coerced);)*
-    };
-
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*; $head:expr, $($tail:expr,)*) => {
-        match &$head {
-            value => {
-                let value_coerced = ::core::convert::AsRef::<::std::path::Path>::as_ref(&value);
-                $additional += value_coerced.as_os_str().len();
-                $crate::path_cat!(@stack $input, $additional; $($values_coerced)* value_coerced; $($tail,)*);
-            }
-        }
-    };
-
-    ($input:expr; $($el:expr),+ $(,)?) => {{
-        #[allow(unused_mut)]
-        let mut input = $input;
-        let mut additional = 0;
-        $crate::path_cat!(@stack input, additional; ; $($el,)*);
-        input
-    }};
-
-    ($($el:expr),+ $(,)?) => {
-        $crate::path_cat!(::std::path::PathBuf::new(); $($el,)*)
+macro_rules! cat {
+    ($($piece:expr),* $(,)?) => {
+        $crate::CatTarget::from_pieces(&[$(::core::convert::AsRef::as_ref(&$piece)),*])
     };
 }
 
-/// Concatenate OS strings for a [`OsString`](std::ffi::OsString).
+/// Concatenate into a thread-local buffer, which is reused once the returned
+/// [`Scratch`](Scratch) is dropped.
 ///
-/// It requires all elements to implement [`AsRef<OsStr>`](AsRef).
+/// It takes the same elements as [`str_cat`](str_cat), and suits strings
+/// that are needed only briefly, such as keys for lookups, which stop
+/// allocating once the buffer is large enough.
 ///
 /// # Example
 ///
 /// ```
-/// use str_cat::os_str_cat;
-/// use std::ffi::OsStr;
-/// use std::path::Path;
+/// use std::collections::HashMap;
+/// use str_cat::scratch_cat;
 ///
-/// let mut s = os_str_cat!("Hello", " ".to_owned(), Path::new("World"), OsStr::new("!"));
-/// assert_eq!(s, OsStr::new("Hello World!"));
+/// let map = HashMap::from([("user:1".to_owned(), "Alice")]);
+/// let mut found = Vec::new();
+/// for id in ["1", "2"] {
+///     let key = scratch_cat!("user:", id);
+///     assert_eq!(*key, format!("user:{id}"));
+///     found.push(map.get(&*key).copied());
+/// }
+/// assert_eq!(found, [Some("Alice"), None]);
 ///
-/// // Reusing allocation.
-/// s.clear();
-/// os_str_cat!(&mut s; "foo", "bar");
-/// assert_eq!(s, OsStr::new("foobar"));
+/// let key = scratch_cat!("user:", "1");
+/// assert_eq!(key, "user:1");
+/// assert_eq!(map[&*key], "Alice");
 /// ```
 #[macro_export]
-macro_rules! os_str_cat {
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*;) => {
-        $input.reserve($additional);
-        $($input.push($values_coerced);)*
-    };
-
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*; $head:expr, $($tail:expr,)*) => {
-        match &$head {
-            value => {
-                let value_coerced = ::core::convert::AsRef::<::std::ffi::OsStr>::as_ref(&value);
-                $additional += value_coerced.len();
-                $crate::os_str_cat!(@stack $input, $additional; $($values_coerced)* value_coerced; $($tail,)*);
-            }
-        }
+macro_rules! scratch_cat {
+    ($($el:tt)*) => {
+        $crate::__cat!(str cat; $crate::Scratch::take(); $($el)*)
     };
+}
 
-    ($input:expr; $($el:expr),+ $(,)?) => {{
-        #[allow(unused_mut)]
-        let mut input = $input;
-        let mut additional = 0;
-        $crate::os_str_cat!(@stack input, additional; ; $($el,)*);
-        input
+/// Concatenate strings for a leaked `&'static str` of the exact length.
+///
+/// It takes the same elements as [`str_cat`](str_cat), without a target, and
+/// suits strings built once that live as long as the program, such as help
+/// texts and names of subcommands.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::leak_cat;
+///
+/// let bin = "mytool";
+/// let about: &'static str = leak_cat!(bin, " ", env!("CARGO_PKG_VERSION"));
+/// assert!(about.starts_with("mytool "));
+/// ```
+#[macro_export]
+macro_rules! leak_cat {
+    ($($el:tt)*) => {{
+        let leaked: &'static str = ::std::boxed::Box::leak($crate::__cat!(str cat; boxed = true; $($el)*));
+        leaked
     }};
-
-    ($($el:expr),+ $(,)?) => {
-        $crate::os_str_cat!(::std::ffi::OsString::new(); $($el,)*)
-    };
 }
 
-/// Concatenate elements for a [`Vec`](Vec).
+/// Concatenate strings once for a `&'static str`, on the first time the call
+/// is reached.
+///
+/// It takes the same elements as [`str_cat`](str_cat), without a target, and
+/// keeps the result in a [`OnceLock`](std::sync::OnceLock) of its own, so the
+/// elements are only evaluated by the first caller, even across threads. The
+/// later calls return the same string without evaluating them again, which
+/// suits values derived from the environment once, like user agents.
 ///
 /// # Example
 ///
 /// ```
-/// use str_cat::vec_cat;
-/// use std::ffi::OsStr;
-/// use std::path::Path;
+/// use str_cat::static_cat;
 ///
-/// let mut s = vec_cat!(b"Hello", b" ", "World".as_bytes(), &[b'!']);
-/// assert_eq!(s, b"Hello World!");
+/// fn user_agent() -> &'static str {
+///     static_cat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"), " (", std::env::consts::OS, ")")
+/// }
 ///
-/// // Reusing allocation.
-/// s.clear();
-/// vec_cat!(&mut s; b"foo", b"bar");
-/// assert_eq!(s, b"foobar");
+/// assert!(user_agent().ends_with(&format!("({})", std::env::consts::OS)));
+/// assert!(std::ptr::eq(user_agent(), user_agent()));
+/// ```
+#[macro_export]
+macro_rules! static_cat {
+    ($($el:tt)*) => {{
+        static CELL: ::std::sync::OnceLock<::std::boxed::Box<str>> = ::std::sync::OnceLock::new();
+        let value: &'static str = CELL.get_or_init(|| $crate::__cat!(str cat; boxed = true; $($el)*));
+        value
+    }};
+}
+
+/// Concatenate into a reused buffer and intern the result, returning the
+/// symbol of the [`Interner`](Interner).
+///
+/// It takes the interner, usually as `&mut interner`, in front of a `;`,
+/// followed by the same elements as [`str_cat`](str_cat). The string is
+/// built like [`scratch_cat`](scratch_cat), so a name that has been interned
+/// before doesn't allocate.
+///
+/// # Leaking
+///
+/// The only [`Interner`](Interner) provided is the one for
+/// `HashSet<&'static str>`, which **leaks every distinct string** it is
+/// given, and never frees it, even when the set is dropped. Only use it for a
+/// bounded set of names that live as long as the program, and implement
+/// [`Interner`](Interner) for an interner of your own otherwise.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use str_cat::intern_cat;
+///
+/// let mut names = HashSet::new();
+/// let a = intern_cat!(&mut names; "get_", "name");
+/// let b = intern_cat!(&mut names; "get", "_name");
+/// assert_eq!(a, "get_name");
+/// assert!(std::ptr::eq(a, b));
+/// assert_eq!(names.len(), 1);
+/// ```
+#[macro_export]
+macro_rules! intern_cat {
+    ($interner:expr; $($el:tt)*) => {
+        $crate::Interner::intern($interner, &$crate::scratch_cat!($($el)*))
+    };
+}
+
+/// Coerce the pieces to an array of `&str` without concatenating them.
+///
+/// Every element is evaluated once and borrowed just like
+/// [`str_cat`](str_cat), which makes it easy to feed the pieces to a sink of
+/// your own. Temporaries in the elements live as long as the array when it is
+/// bound with `let`.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::pieces;
+///
+/// let n = 42;
+/// let name = "answer".to_owned();
+/// let pieces = pieces!(name, " = ", n.to_string());
+/// assert_eq!(pieces, ["answer", " = ", "42"]);
+///
+/// let mut hasher = std::collections::hash_map::DefaultHasher::new();
+/// for piece in pieces {
+///     std::hash::Hasher::write(&mut hasher, piece.as_bytes());
+/// }
+/// ```
+#[macro_export]
+macro_rules! pieces {
+    () => {
+        [] as [&str; 0]
+    };
+    ($($piece:expr),* $(,)?) => {
+        [$(&$piece as &str),*]
+    };
+}
+
+/// Chain the pieces into a [`Chain`](Chain) without concatenating them.
+///
+/// It requires all elements to dereference to [`str`](str), and borrows them
+/// just like [`str_cat`](str_cat). Temporaries in the elements live as long
+/// as the chain when it is bound with `let`.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::chain_cat;
+///
+/// let n = 42;
+/// let name = "answer".to_owned();
+/// let chain = chain_cat!(name, " = ", n.to_string());
+/// assert_eq!(chain, "answer = 42");
+/// assert_eq!(chain.len(), 11);
+/// assert_eq!(chain.to_string(), "answer = 42");
+/// assert!(chain.chars().rev().eq("24 = rewsna".chars()));
+///
+/// // Concatenating in the end.
+/// let s = String::from(chain);
+/// assert_eq!(s, "answer = 42");
+/// ```
+///
+/// With the `serde` feature, a chain is serialized as a string without being
+/// concatenated first.
+///
+#[cfg_attr(feature = "serde", doc = "```")]
+#[cfg_attr(not(feature = "serde"), doc = "```ignore")]
+/// use str_cat::chain_cat;
+///
+/// let host = "example.com";
+/// let chain = chain_cat!("https://", host, "/");
+/// assert_eq!(serde_json::to_string(&chain).unwrap(), r#""https://example.com/""#);
+/// ```
+#[macro_export]
+macro_rules! chain_cat {
+    ($($piece:expr),* $(,)?) => {
+        $crate::Chain {
+            __pieces: &$crate::pieces!($($piece),*),
+        }
+    };
+}
+
+/// Concatenate paths for a [`PathBuf`](std::path::PathBuf).
+///
+/// It requires all elements to implement [`AsRef<Path>`](AsRef). On Unix,
+/// byte slices and arrays are taken as raw bytes as well.
+///
+/// With a `normalize = true` option, `.` components are dropped and `..`
+/// components are resolved against the components before them. This is
+/// purely lexical, so it may disagree with the file system when symlinks are
+/// involved. It goes along with the other options, such as `reserve_extra`
+/// and `fit`.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::path_cat;
+/// use std::ffi::OsStr;
+/// use std::path::{Path, PathBuf};
+///
+/// let mut s = path_cat!("Hello", "space".to_owned(), Path::new("World"), OsStr::new("bang"));
+/// assert_eq!(s, ["Hello", "space", "World", "bang"].iter().collect::<PathBuf>());
+///
+/// // Reusing allocation.
+/// s.clear();
+/// path_cat!(&mut s; "foo", "bar");
+/// assert_eq!(s, ["foo", "bar"].iter().collect::<PathBuf>());
+///
+/// // Conditional elements.
+/// let sub_dir = Some("logs");
+/// let s = path_cat!("var", @when(let Some(d) = sub_dir, d), @when(false, "tmp"), "app");
+/// assert_eq!(s, ["var", "logs", "app"].iter().collect::<PathBuf>());
+///
+/// // Reserving once, separators included.
+/// let parts = ["usr", "local", "share", "applications"];
+/// let mut s = PathBuf::new();
+/// path_cat!(&mut s; parts[0], parts[1], parts[2], parts[3]);
+/// let mut reserved = PathBuf::new();
+/// reserved.reserve(parts.iter().map(|p| p.len() + 1).sum());
+/// assert_eq!(s.capacity(), reserved.capacity());
+///
+/// // Normalizing lexically, dropping `.` and resolving `..`.
+/// let s = path_cat!(normalize = true; "/srv/www/", "./static//", "../uploads", "a.png");
+/// assert_eq!(s, Path::new("/srv/www/uploads/a.png"));
+/// let mut s = PathBuf::from("/srv");
+/// path_cat!(&mut s, normalize = true; "..", "../etc");
+/// assert_eq!(s, Path::new("/etc"));
+/// let mut s = PathBuf::from("/srv/www");
+/// let len = path_cat!(&mut s, normalize = true, count = true; "./static", "../uploads");
+/// assert_eq!((s.as_os_str().len(), len), (16, 8));
+/// let mut s = PathBuf::new();
+/// path_cat!(&mut s, normalize = true, reserve_extra = 8; "a", "./b");
+/// assert!(s.capacity() >= 3 + 8);
+/// let s = path_cat!(fit = true, normalize = true; "a", "../b");
+/// assert_eq!(s, Path::new("b"));
+/// let normalize = PathBuf::from("a");
+/// assert_eq!(path_cat!(normalize; "b"), Path::new("a/b"));
+///
+/// // Splicing filtered components.
+/// use std::path::Component;
+/// let src = Path::new("/home/me/.cache/app/data.db");
+/// let kept = src.components().filter(|c| *c != Component::Normal(".cache".as_ref()));
+/// let s = path_cat!("/backup", @iter(kept.skip(1)), "v2");
+/// assert_eq!(s, Path::new("/backup/home/me/app/data.db/v2"));
+/// ```
+///
+/// ```
+/// # #[cfg(unix)] {
+/// use str_cat::{path_cat, vec_cat};
+/// use std::os::unix::ffi::OsStrExt;
+///
+/// // A file name received over a byte protocol, and sent back.
+/// let name: Vec<u8> = b"caf\xe9.txt".to_vec();
+/// let path = path_cat!("/srv/files", name);
+/// assert_eq!(path.as_os_str().as_bytes(), b"/srv/files/caf\xe9.txt");
+/// let reply = vec_cat!(b"OK ", path, b"\n");
+/// assert_eq!(reply, b"OK /srv/files/caf\xe9.txt\n");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! path_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(path cat; $($tt)*)
+    };
+}
+
+/// Decode and concatenate UTF-16 for a [`String`](String).
+///
+/// It requires all elements to implement [`AsRef<[u16]>`](AsRef), such as
+/// the wide strings returned by Windows APIs. Every piece is decoded while
+/// being copied, with the decoded length counted into the single reserve.
+/// Unpaired surrogates are replaced by U+FFFD like
+/// [`String::from_utf16_lossy`](String::from_utf16_lossy), while
+/// `@strict(piece)` propagates the
+/// [`DecodeUtf16Error`](std::char::DecodeUtf16Error) with `?` instead.
+///
+/// # Example
+///
+/// ```
+/// use std::char::DecodeUtf16Error;
+/// use str_cat::utf16_cat;
+///
+/// let dir: Vec<u16> = "C:\\Users\\".encode_utf16().collect();
+/// let name: Vec<u16> = "名前".encode_utf16().collect();
+/// let s = utf16_cat!(dir, name, [0xd800]);
+/// assert_eq!(s, "C:\\Users\\名前\u{fffd}");
+///
+/// fn decode(pieces: &[&[u16]]) -> Result<String, DecodeUtf16Error> {
+///     Ok(utf16_cat!(@strict(pieces[0]), @strict(pieces[1])))
+/// }
+/// assert_eq!(decode(&[&dir, &name]).unwrap(), "C:\\Users\\名前");
+/// assert!(decode(&[&dir, &[0xd800]]).is_err());
+/// ```
+#[macro_export]
+macro_rules! utf16_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(utf16 cat; $($tt)*)
+    };
+}
+
+/// Concatenate strings for an [`InlineString`](InlineString) without
+/// allocating, failing with a [`CapacityError`](CapacityError) if they don't
+/// fit.
+///
+/// It takes the same elements as [`str_cat`](str_cat). The capacity `N` of a
+/// new `InlineString<N>` is inferred from the context. The total length is
+/// checked before pushing anything, and in case pieces that are not counted
+/// upfront, like [`format_args!`](format_args), don't fit, the target is
+/// truncated back, so the target is left untouched on failure.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::{inline_str_cat, CapacityError, InlineString};
+///
+/// let shard = 7;
+/// let key: InlineString<16> = inline_str_cat!("user:", @display(shard), ":name").unwrap();
+/// assert_eq!(key, "user:7:name");
+///
+/// let long = "a".repeat(20);
+/// let res: Result<InlineString<16>, _> = inline_str_cat!("user:", long);
+/// assert_eq!(res, Err(CapacityError));
+///
+/// // Appending to an existing one.
+/// let mut s = InlineString::<8>::try_from("ab").unwrap();
+/// inline_str_cat!(&mut s; "cd").unwrap();
+/// assert!(inline_str_cat!(&mut s; "ef", format_args!("{}", 1234)).is_err());
+/// assert_eq!(s, "abcd");
+/// ```
+#[macro_export]
+macro_rules! inline_str_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(str inline; $($tt)*)
+    };
+}
+
+/// Concatenate OS strings for a [`OsString`](std::ffi::OsString).
+///
+/// It requires all elements to implement [`AsRef<OsStr>`](AsRef). On Unix,
+/// elements that dereference to a [`CStr`](std::ffi::CStr) are taken without
+/// the NUL, and byte slices and arrays are taken as raw bytes. On Windows, wide strings that implement
+/// [`AsRef<[u16]>`](AsRef) are taken as well, each of which is converted by
+/// `OsString::from_wide` once and then counted into the capacity reserved for
+/// all elements.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::os_str_cat;
+/// use std::ffi::OsStr;
+/// use std::path::Path;
+///
+/// let mut s = os_str_cat!("Hello", " ".to_owned(), Path::new("World"), OsStr::new("!"));
+/// assert_eq!(s, OsStr::new("Hello World!"));
+///
+/// // Reusing allocation.
+/// s.clear();
+/// os_str_cat!(&mut s; "foo", "bar");
+/// assert_eq!(s, OsStr::new("foobar"));
+/// ```
+///
+/// ```
+/// # #[cfg(unix)] {
+/// use str_cat::os_str_cat;
+/// use std::ffi::{CString, OsStr};
+///
+/// use std::os::unix::ffi::OsStrExt;
+///
+/// let name = CString::new("lib").unwrap();
+/// let s = os_str_cat!(name, c".so.", c"1");
+/// assert_eq!(s, OsStr::new("lib.so.1"));
+///
+/// // Raw bytes, which need not be UTF-8.
+/// let s = os_str_cat!("/tmp/", b"caf\xe9", ".txt");
+/// assert_eq!(s.as_bytes(), b"/tmp/caf\xe9.txt");
+/// # }
+/// ```
+///
+/// ```
+/// # #[cfg(windows)] {
+/// use str_cat::os_str_cat;
+/// use std::ffi::OsStr;
+///
+/// let dir: Vec<u16> = "C:\\Users".encode_utf16().collect();
+/// let s = os_str_cat!(dir, "\\", [0x540d, 0x524d]);
+/// assert_eq!(s, OsStr::new("C:\\Users\\名前"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! os_str_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(os_str cat; $($tt)*)
+    };
+}
+
+/// Concatenate elements for a [`Vec`](Vec).
+///
+/// It requires all elements to implement [`AsRef<[T]>`](AsRef). For a
+/// `Vec<u8>`, elements that dereference to a [`CStr`](std::ffi::CStr) are
+/// taken without the NUL, and on Unix, elements that dereference to an
+/// [`OsStr`](std::ffi::OsStr) or a [`Path`](std::path::Path) are taken as
+/// their bytes.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::vec_cat;
+/// use std::ffi::OsStr;
+/// use std::path::Path;
+///
+/// let mut s = vec_cat!(b"Hello", b" ", "World".as_bytes(), &[b'!']);
+/// assert_eq!(s, b"Hello World!");
+///
+/// // Reusing allocation.
+/// s.clear();
+/// vec_cat!(&mut s; b"foo", b"bar");
+/// assert_eq!(s, b"foobar");
+///
+/// // Array repeat expressions are plain pieces.
+/// let s = vec_cat!(b"a", [0u8; 4]);
+/// assert_eq!(s, [b'a', 0, 0, 0, 0]);
+///
+/// // C strings, without the NUL.
+/// let name = std::ffi::CString::new("eth").unwrap();
+/// let s = vec_cat!(name, c"0", b"\0");
+/// assert_eq!(s, b"eth0\0");
 /// ```
 #[macro_export]
 macro_rules! vec_cat {
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*;) => {
+    ($($tt:tt)*) => {
+        $crate::__cat!(vec cat; $($tt)*)
+    };
+}
+
+/// Join strings with a separator for a [`String`](String).
+///
+/// The part before `;` takes an optional target, like [`str_cat`](str_cat),
+/// followed by options:
+///
+/// - `sep = expr`: the separator pushed between every two pieces.
+/// - `last_sep = expr`: the separator pushed before the last piece instead of
+///   `sep`.
+/// - `skip_empty = bool`: leave out empty pieces, along with their separators.
+/// - `prefix = expr`: pushed once before all pieces.
+/// - `suffix = expr`: pushed once after all pieces.
+/// - `terminator = expr`: pushed after every piece, including the last one.
+/// - `crlf = true`: shorthand for `terminator = "\r\n"`.
+///
+/// The prefix and suffix are pushed even if there is no piece at all.
+///
+/// Separators are counted into the single reserve. Elements that are left
+/// out by a condition don't get a separator either.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::str_join;
+///
+/// let mut s = str_join!(sep = ", "; "a", "", "b");
+/// assert_eq!(s, "a, , b");
+///
+/// // Skipping empty pieces.
+/// s.clear();
+/// let extra = "";
+/// str_join!(&mut s, sep = " ", skip_empty = true; "btn", extra, "btn-primary", @when(false, "active"));
+/// assert_eq!(s, "btn btn-primary");
+///
+/// // Repeated elements are separated too.
+/// let s = str_join!(sep = ", "; @repeat("?", 3));
+/// assert_eq!(s, "?, ?, ?");
+///
+/// // A list for humans.
+/// let s = str_join!(sep = ", ", last_sep = " and "; "a", "b", "c");
+/// assert_eq!(s, "a, b and c");
+///
+/// // Wrapping the list.
+/// let s = str_join!(sep = ", ", prefix = "[", suffix = "]\n"; "1", "2", "3");
+/// assert_eq!(s, "[1, 2, 3]\n");
+///
+/// // One piece per line.
+/// let s = str_join!(terminator = "\n"; "foo", "bar");
+/// assert_eq!(s, "foo\nbar\n");
+/// ```
+#[macro_export]
+macro_rules! str_join {
+    ($($tt:tt)*) => {
+        $crate::__cat!(str join; $($tt)*)
+    };
+}
+
+/// Concatenate strings for a [`String`](String), one piece per line.
+///
+/// Every piece is followed by `"\n"`, or `"\r\n"` with `crlf = true`, and
+/// the terminators are counted into the single reserve. It takes the same
+/// options as [`str_join`](str_join), where `terminator` defaults to `"\n"`,
+/// and the same elements as [`str_cat`](str_cat).
+///
+/// # Example
+///
+/// ```
+/// use str_cat::lines_cat;
+///
+/// let name = "str-cat";
+/// let s = lines_cat!("[package]", @display(format_args!("name = {name:?}")));
+/// assert_eq!(s, "[package]\nname = \"str-cat\"\n");
+///
+/// let mut head = "HTTP/1.1 200 OK\r\n".to_owned();
+/// lines_cat!(&mut head, crlf = true; "Content-Type: text/plain", "Content-Length: 0", "");
+/// assert_eq!(
+///     head,
+///     "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 0\r\n\r\n",
+/// );
+/// ```
+#[macro_export]
+macro_rules! lines_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(str lines; $($tt)*)
+    };
+}
+
+/// Concatenate strings for a [`String`](String), indenting every line.
+///
+/// The part before `;` takes an optional target, like [`str_cat`](str_cat),
+/// followed by `indent = expr`. The indent is written at the start of every
+/// non-empty line, including the first one if the target is empty or ends
+/// with a newline, so that subsequent calls continue the indentation
+/// seamlessly. Empty lines are left untouched to avoid trailing whitespace.
+///
+/// The pieces are streamed into the target without being split and joined.
+/// Their newlines are counted upfront so that the single reserve includes the
+/// inserted indents.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::indent_cat;
+///
+/// let body = "let x = 1;\n\nx + 1\n";
+/// let mut s = "fn foo() -> i32 {\n".to_owned();
+/// indent_cat!(&mut s, indent = "    "; body);
+/// s.push_str("}\n");
+/// assert_eq!(s, "fn foo() -> i32 {\n    let x = 1;\n\n    x + 1\n}\n");
+///
+/// // Lines can span several pieces.
+/// let name = "bar";
+/// let s = indent_cat!(indent = "  "; "fn ", name, "() {}\n", "struct ", name, ";");
+/// assert_eq!(s, "  fn bar() {}\n  struct bar;");
+/// ```
+#[macro_export]
+macro_rules! indent_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(str indent; $($tt)*)
+    };
+}
+
+/// Concatenate strings for a [`String`](String), removing the common leading
+/// whitespace of every line, like Python's `textwrap.dedent`.
+///
+/// The common indentation is the minimum number of leading spaces and tabs
+/// among the lines that are not whitespace-only, counted across all pieces.
+/// Then that many leading spaces and tabs are removed from every line.
+///
+/// The pieces are walked twice, once to measure the indentation along with
+/// the capacity and once to push, so no intermediate string is allocated.
+/// That also means a `format_args!` or `@display` piece is formatted twice.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::dedent_cat;
+///
+/// let name = "world";
+/// let s = dedent_cat!(
+///     "
+///     fn hello() {
+///         println!(\"Hello, ", name, "!\");
+///     }
+///     ",
+/// );
+/// assert_eq!(s, "\nfn hello() {\n    println!(\"Hello, world!\");\n}\n");
+///
+/// // Appending to an existing string.
+/// let mut s = "text:\n".to_owned();
+/// dedent_cat!(&mut s; "  foo\n", "    bar\n");
+/// assert_eq!(s, "text:\nfoo\n  bar\n");
+/// ```
+#[macro_export]
+macro_rules! dedent_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(str dedent; $($tt)*)
+    };
+}
+
+/// Concatenate strings for a [`String`](String), escaping the pieces marked
+/// with `@esc(expr)` as the content of a JSON string.
+///
+/// `"`, `\\` and control characters are escaped while copying, other pieces
+/// are pushed verbatim. The escaped length is measured upfront so that the
+/// single reserve is exact.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::json_str_cat;
+///
+/// let name = "Alice \"Al\" Smith";
+/// let note = "line 1\nline 2\t\u{1}";
+/// let s = json_str_cat!(r#"{"name":""#, @esc(name), r#"","note":""#, @esc(note), r#""}"#);
+/// assert_eq!(s, r#"{"name":"Alice \"Al\" Smith","note":"line 1\nline 2\t\u0001"}"#);
+/// ```
+#[macro_export]
+macro_rules! json_str_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(json cat; $($tt)*)
+    };
+}
+
+/// Concatenate strings for a [`String`](String), escaping the pieces marked
+/// with `@esc(expr)` for HTML and XML.
+///
+/// `&`, `<`, `>`, `"` and `'` are replaced with entities while copying, so
+/// the escaped pieces are safe in both text and quoted attribute values.
+/// Other pieces are pushed verbatim. The escaped length is measured upfront
+/// so that the single reserve is exact.
+///
+/// Requires the `html` feature.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::html_cat;
+///
+/// let title = "Tom & Jerry's <show>";
+/// let href = "/search?q=\"cat\"";
+/// let s = html_cat!(r#"<a href=""#, @esc(href), r#"">"#, @esc(title), "</a>");
+/// assert_eq!(
+///     s,
+///     "<a href=\"/search?q=&quot;cat&quot;\">Tom &amp; Jerry&#39;s &lt;show&gt;</a>",
+/// );
+/// ```
+#[cfg(feature = "html")]
+#[macro_export]
+macro_rules! html_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(html cat; $($tt)*)
+    };
+}
+
+/// Concatenate SQL for a [`String`](String), quoting the pieces marked with
+/// `@ident(expr)` as identifiers and `@lit(expr)` as string literals.
+///
+/// An identifier is enclosed in double quotes, or in another quote such as a
+/// backtick for MySQL with `@ident(expr, '`')`, and a literal in single
+/// quotes, with every quote in it doubled while copying. Other pieces are
+/// pushed verbatim. The quoted length is measured upfront so that the single
+/// reserve is exact.
+///
+/// Backslashes are not escaped, as in standard SQL, so a literal is only safe
+/// for MySQL with the `NO_BACKSLASH_ESCAPES` mode. Bind parameters are still
+/// preferred for values whenever the driver supports them.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::sql_cat;
+///
+/// let table = "user \"data\"";
+/// let name = "O'Brien";
+/// let s = sql_cat!("SELECT * FROM ", @ident(table), " WHERE name = ", @lit(name));
+/// assert_eq!(s, r#"SELECT * FROM "user ""data""" WHERE name = 'O''Brien'"#);
+///
+/// let s = sql_cat!("DROP TABLE ", @ident("a`b", '`'));
+/// assert_eq!(s, "DROP TABLE `a``b`");
+/// ```
+#[macro_export]
+macro_rules! sql_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(sql cat; $($tt)*)
+    };
+}
+
+/// Build a CSV record for a [`String`](String), quoting fields as per
+/// [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180).
+///
+/// Every element is a field. A field is enclosed in double quotes only when
+/// it contains the separator, a double quote, CR or LF, in which case its
+/// double quotes are doubled. Fields are joined by the separator and the
+/// record terminator is appended, all in one pass with one reserve, which
+/// makes it suitable for reusing a row buffer.
+///
+/// It takes the same options as [`str_join`](str_join), where `sep` defaults
+/// to `","` and `suffix`, the record terminator, defaults to `"\r\n"`.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::csv_cat;
+///
+/// let mut row = String::new();
+/// for (name, note) in [("Alice", "likes \"cats\""), ("Bob", "a, b")] {
+///     row.clear();
+///     csv_cat!(&mut row; name, note, "42");
+///     // write `row` somewhere
+/// #   assert!(row.ends_with("42\r\n"));
+/// }
+/// assert_eq!(row, "Bob,\"a, b\",42\r\n");
+///
+/// let s = csv_cat!(sep = "\t", suffix = "\n"; "a\tb", "c, d", "e\nf");
+/// assert_eq!(s, "\"a\tb\"\tc, d\t\"e\nf\"\n");
+///
+/// let s = csv_cat!("Alice", "likes \"cats\"");
+/// assert_eq!(s, "Alice,\"likes \"\"cats\"\"\"\r\n");
+/// ```
+#[macro_export]
+macro_rules! csv_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(csv join; $($tt)*)
+    };
+}
+
+/// Append to a [`PathBuf`](std::path::PathBuf) without inserting separators.
+///
+/// Unlike [`path_cat`](path_cat), which adds every element as a new
+/// component, this extends the last component with the raw bytes of every
+/// element. It requires all elements to implement
+/// [`AsRef<OsStr>`](AsRef).
+///
+/// # Example
+///
+/// ```
+/// use str_cat::path_append;
+/// use std::path::{Path, PathBuf};
+///
+/// let s = path_append!("report", "-v2", ".txt");
+/// assert_eq!(s, Path::new("report-v2.txt"));
+///
+/// let mut s = PathBuf::from("logs").join("app");
+/// let n = 3;
+/// path_append!(&mut s; ".", n.to_string(), ".log");
+/// assert_eq!(s, Path::new("logs").join("app.3.log"));
+/// ```
+#[macro_export]
+macro_rules! path_append {
+    ($($tt:tt)*) => {
+        $crate::__cat!(path_raw cat; $($tt)*)
+    };
+}
+
+/// Build a file name for a [`PathBuf`](std::path::PathBuf) like
+/// [`path_append`](path_append), with an optional directory in front and an
+/// optional extension after.
+///
+/// The directory goes before the first `;`, and is followed by a separator
+/// unless it is empty. The extension goes after the second `;` as
+/// `ext = "log"`, without the dot.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::file_name_cat;
+/// use std::path::Path;
+///
+/// let dir = Path::new("/var/log");
+/// let timestamp = 20240101;
+/// let s = file_name_cat!(dir; "app", "-", timestamp.to_string(); ext = "log");
+/// assert_eq!(s, Path::new("/var/log/app-20240101.log"));
+///
+/// let s = file_name_cat!("backup", "-", "2"; ext = "tar.gz");
+/// assert_eq!(s, Path::new("backup-2.tar.gz"));
+///
+/// let s = file_name_cat!("data"; "notes", ".txt");
+/// assert_eq!(s, Path::new("data").join("notes.txt"));
+/// ```
+#[macro_export]
+macro_rules! file_name_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(file_name file_name; $($tt)*)
+    };
+}
+
+/// Like [`path_cat`](path_cat), but rejecting components that would replace
+/// the path built so far.
+///
+/// [`PathBuf::push`](std::path::PathBuf::push) replaces the whole path with
+/// an absolute component, or a rooted one or one with a prefix on Windows.
+/// Instead, this evaluates to `Result<T, StrictPathError>`, where `T` is the
+/// type of the target, and fails without pushing anything if such a component
+/// comes after anything non-empty.
+///
+/// # Example
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+/// use str_cat::strict_path_cat;
+///
+/// let user_input = "/etc/passwd";
+/// let root = Path::new("/srv/files");
+/// assert!(strict_path_cat!(root, "uploads", user_input).is_err());
+///
+/// let user_input = "avatar.png";
+/// let path = strict_path_cat!(root, "uploads", user_input).unwrap();
+/// assert_eq!(path, Path::new("/srv/files/uploads/avatar.png"));
+///
+/// // Appending to an existing `PathBuf`.
+/// let mut path = PathBuf::from("/srv");
+/// assert!(strict_path_cat!(&mut path; "files", "/tmp").is_err());
+/// assert_eq!(path, Path::new("/srv"));
+/// ```
+#[macro_export]
+macro_rules! strict_path_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(path strict; $($tt)*)
+    };
+}
+
+/// Join path entries for an [`OsString`](std::ffi::OsString) with the
+/// separator of `PATH` and the like, which is `;` on Windows and `:`
+/// elsewhere.
+///
+/// Unlike [`std::env::join_paths`], it can append to an existing list, with a
+/// separator in between if the list is not empty. The entries are anything
+/// that [`os_str_cat`](os_str_cat) takes, and it takes the same options as
+/// [`str_join`](str_join).
+///
+/// It evaluates to `Result<T, JoinPathsError>`, where `T` is the type of the
+/// target. If any entry contains the separator, it fails without pushing
+/// anything.
+///
+/// # Example
+///
+/// ```
+/// use std::ffi::OsString;
+/// use std::path::Path;
+/// use str_cat::env_paths_cat;
+///
+/// let sep = if cfg!(windows) { ";" } else { ":" };
+/// let home = Path::new("/home/user");
+/// let s = env_paths_cat!(home.join("bin"), "/usr/local/bin").unwrap();
+/// assert_eq!(s, OsString::from(format!("/home/user/bin{sep}/usr/local/bin")));
+///
+/// let mut path = OsString::from("/usr/bin");
+/// env_paths_cat!(&mut path; "/opt/bin").unwrap();
+/// assert_eq!(path, OsString::from(format!("/usr/bin{sep}/opt/bin")));
+///
+/// let bad = format!("/a{sep}/b");
+/// assert!(env_paths_cat!(&mut path; "/sbin", bad).is_err());
+/// assert_eq!(path, OsString::from(format!("/usr/bin{sep}/opt/bin")));
+/// ```
+#[macro_export]
+macro_rules! env_paths_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(env_paths env_paths; $($tt)*)
+    };
+}
+
+/// Build a command line for an [`OsString`](std::ffi::OsString), with
+/// `@quote(s)` elements quoted as single shell words.
+///
+/// Other elements are pushed verbatim, so they can be anything that
+/// [`os_str_cat`](os_str_cat) takes. A quoted `s` must be a `&str`, which is
+/// quoted for `sh -c` everywhere but Windows, and for `CommandLineToArgvW` on
+/// Windows.
+///
+/// On Windows, the quoting is only for programs started directly, such as with
+/// [`CommandExt::raw_arg`](https://doc.rust-lang.org/std/os/windows/process/trait.CommandExt.html#tymethod.raw_arg).
+/// It does not escape the metacharacters of `cmd.exe`, like `&`, `|`, `<`,
+/// `>`, `^` and `%`, so the command line is **not** safe to pass to `cmd /C`
+/// or to run as a batch file with untrusted input.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::shell_cat;
+///
+/// let msg = "it's done";
+/// let s = shell_cat!("git commit -m ", @quote(msg), " -- ", @quote("src/lib.rs"));
+/// if cfg!(windows) {
+///     assert_eq!(s, r#"git commit -m "it's done" -- src/lib.rs"#);
+/// } else {
+///     assert_eq!(s, r"git commit -m 'it'\''s done' -- src/lib.rs");
+/// }
+///
+/// // Appending to an existing `OsString`.
+/// let mut s = std::ffi::OsString::from("echo");
+/// shell_cat!(&mut s; " ", @quote(""));
+/// if cfg!(windows) {
+///     assert_eq!(s, r#"echo """#);
+/// } else {
+///     assert_eq!(s, "echo ''");
+/// }
+///
+/// // `^` is a pipe in some shells, so it is quoted too.
+/// if !cfg!(windows) {
+///     assert_eq!(shell_cat!(@quote("HEAD^")), "'HEAD^'");
+/// }
+/// ```
+#[macro_export]
+macro_rules! shell_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(shell cat; $($tt)*)
+    };
+}
+
+/// Build a query string for a [`String`](String) from `key = value` pairs.
+///
+/// Keys are identifiers or string literals, and values are anything that
+/// dereferences to [`str`](str), or an [`Option`](Option) of it. Both are
+/// percent-encoded, keeping only the unreserved characters of RFC 3986.
+/// Pairs whose value is `None` are left out. Other elements are pushed
+/// verbatim as already encoded pairs.
+///
+/// It takes the same options as [`str_join`](str_join), where `sep` defaults
+/// to `"&"` and `skip_empty` is always on.
+///
+/// Requires the `percent-encoding` feature.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::query_cat;
+///
+/// let q = "rust & café";
+/// let page = 2;
+/// let sort = None::<&str>;
+/// let s = query_cat!(q = q, page = page.to_string(), sort = sort, "filter[lang]" = Some("en"));
+/// assert_eq!(s, "q=rust%20%26%20caf%C3%A9&page=2&filter%5Blang%5D=en");
+///
+/// // Appending to a URL.
+/// let mut url = "https://example.com/search".to_owned();
+/// query_cat!(&mut url, prefix = "?"; q = "str-cat", "raw=1");
+/// assert_eq!(url, "https://example.com/search?q=str-cat&raw=1");
+/// ```
+#[cfg(feature = "percent-encoding")]
+#[macro_export]
+macro_rules! query_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(query join; $($tt)*)
+    };
+}
+
+/// Build a URL for a [`String`](String) from a base and path segments,
+/// followed by query pairs and a fragment.
+///
+/// Path segments are anything that dereferences to [`str`](str), and are
+/// joined with exactly one `/`, dropping the leading slashes of every segment
+/// after the base, while a trailing slash of the last segment is kept. Empty
+/// segments are left out. Segments go before a query or a fragment that the
+/// base has already. `@enc(segment)` percent-encodes a segment like the
+/// element of [`str_cat`](str_cat), so that a `/` in it is kept in the
+/// segment.
+///
+/// `key = value` pairs are percent-encoded like [`query_cat`](query_cat),
+/// where a `None` value leaves out the pair, and the first pair starts the
+/// query with a `?` unless there is one already. Pairs go before a fragment
+/// that the base has already. `@fragment(s)` pushes `s` verbatim after a `#`,
+/// replacing the fragment of the base.
+///
+/// The capacity is reserved once, counting a separator before every element.
+///
+/// Requires the `percent-encoding` feature.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::url_cat;
+///
+/// let base = "https://api.example.com/";
+/// let user = "ferris/crab";
+/// let s = url_cat!(base, "/v1/", "users", @enc(user), "repos/");
+/// assert_eq!(s, "https://api.example.com/v1/users/ferris%2Fcrab/repos/");
+///
+/// let page = 2.to_string();
+/// let sort = None::<&str>;
+/// let s = url_cat!(
+///     base, "search",
+///     q = "rust & café", page = page, sort = sort,
+///     @fragment("results"),
+/// );
+/// assert_eq!(s, "https://api.example.com/search?q=rust%20%26%20caf%C3%A9&page=2#results");
+///
+/// // Appending to a URL that has a query already.
+/// let mut s = "https://example.com/?lang=en".to_owned();
+/// url_cat!(&mut s; raw = "1");
+/// assert_eq!(s, "https://example.com/?lang=en&raw=1");
+///
+/// // Continuing a query that ends in `?`, before the fragment of the base.
+/// let mut s = "https://example.com/?#top".to_owned();
+/// url_cat!(&mut s; lang = "en", raw = "1");
+/// assert_eq!(s, "https://example.com/?lang=en&raw=1#top");
+///
+/// // Segments go before the query of the base.
+/// let s = url_cat!("https://example.com/?lang=en", "docs", raw = "1");
+/// assert_eq!(s, "https://example.com/docs?lang=en&raw=1");
+///
+/// // And before its fragment, which `@fragment` replaces.
+/// let s = url_cat!("https://example.com/guide#top", "intro", @fragment("usage"));
+/// assert_eq!(s, "https://example.com/guide/intro#usage");
+/// ```
+#[cfg(feature = "percent-encoding")]
+#[macro_export]
+macro_rules! url_cat {
+    ($($tt:tt)*) => {
+        $crate::__cat!(url url; $($tt)*)
+    };
+}
+
+/// Concatenate with [`format!`](format)-like interpolation for a
+/// [`String`](String), expanding to [`str_cat`](str_cat).
+///
+/// Text outside the braces is pushed as is, while the holes are written with
+/// [`Display`](std::fmt::Display) like `@display(value)` in `str_cat`, without
+/// an intermediate `String`. A hole is either `{name}`, capturing a variable
+/// in scope, or `{}` or `{0}`, taking the arguments after the format string.
+/// A hole with a format spec like `{n:>4}` is written through
+/// [`format_args!`](format_args). `{{` and `}}` are escaped braces.
+///
+/// Like `str_cat`, it takes an optional target in front of a `;`.
+///
+/// Requires the `fmt` feature.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::cat_fmt;
+///
+/// let name = "Ferris";
+/// let n = 3;
+/// let s = cat_fmt!("Hello {name}, you have {n} items");
+/// assert_eq!(s, "Hello Ferris, you have 3 items");
+///
+/// let mut s = String::from("[");
+/// cat_fmt!(&mut s; "{}-{0}: {n:03} {{ok}}]", name.len());
+/// assert_eq!(s, "[6-6: 003 {ok}]");
+/// ```
+#[cfg(feature = "fmt")]
+#[macro_export]
+macro_rules! cat_fmt {
+    ($($tt:tt)*) => {
+        $crate::__private::cat_fmt!([$crate] $($tt)*)
+    };
+}
+
+/// The shared implementation of the concatenating macros.
+///
+/// `$kind` selects how elements are coerced, measured and pushed. The input
+/// is first scanned for a top-level `;` that separates the target from the
+/// elements, so that elements are free to use syntax that does not parse as
+/// an expression on its own.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cat {
+    (@new str) => { ::std::string::String::new() };
+    (@new path) => { ::std::path::PathBuf::new() };
+    (@new os_str) => { ::std::ffi::OsString::new() };
+    (@new vec) => { ::std::vec::Vec::new() };
+    (@new json) => { ::std::string::String::new() };
+    (@new html) => { ::std::string::String::new() };
+    (@new sql) => { ::std::string::String::new() };
+    (@new csv) => { ::std::string::String::new() };
+    (@new query) => { ::std::string::String::new() };
+    (@new url) => { ::std::string::String::new() };
+    (@new shell) => { ::std::ffi::OsString::new() };
+    (@new env_paths) => { ::std::ffi::OsString::new() };
+    (@new path_raw) => { ::std::path::PathBuf::new() };
+    (@new utf16) => { ::std::string::String::new() };
+
+    (@unsized str) => { str };
+    (@unsized os_str) => { ::std::ffi::OsStr };
+    (@unsized vec) => { [_] };
+    (@unsized csv) => { str };
+    (@unsized query) => { str };
+    (@unsized env_paths) => { ::std::ffi::OsStr };
+
+    (@join_defaults csv) => { $crate::__private::JoinOptions::csv() };
+    (@join_defaults query) => { $crate::__private::JoinOptions::query() };
+    (@join_defaults env_paths) => { $crate::__private::JoinOptions::env_paths() };
+    (@join_defaults $kind:ident) => { ::core::default::Default::default() };
+
+    (@coerce str $value:ident) => { $crate::__private::as_str($value) };
+    (@coerce path $value:ident) => { $crate::__private::as_path($value) };
+    (@coerce os_str $value:ident) => { $crate::__private::to_os_str($value) };
+    (@coerce vec $value:ident) => { $crate::__private::as_slice($value) };
+    (@coerce json $value:ident) => { $crate::__cat!(@coerce str $value) };
+    (@coerce html $value:ident) => { $crate::__cat!(@coerce str $value) };
+    (@coerce sql $value:ident) => { $crate::__cat!(@coerce str $value) };
+    (@coerce csv $value:ident) => { $crate::__cat!(@coerce str $value) };
+    (@coerce query $value:ident) => { $crate::__cat!(@coerce str $value) };
+    (@coerce url $value:ident) => { $crate::__cat!(@coerce str $value) };
+    (@coerce shell $value:ident) => { $crate::__cat!(@coerce os_str $value) };
+    (@coerce env_paths $value:ident) => { $crate::__private::as_os_str($value) };
+    (@coerce path_raw $value:ident) => { $crate::__private::as_os_str($value) };
+    (@coerce utf16 $value:ident) => {
+        $crate::__private::Utf16::lossy(::core::convert::AsRef::<[u16]>::as_ref($value))
+    };
+
+    (@len piece $value_coerced:expr) => { $crate::__private::Piece::len(&$value_coerced) };
+    (@len os_piece $value_coerced:expr) => { $crate::__private::Piece::len(&$value_coerced) };
+    (@len utf16 $value_coerced:expr) => { $crate::__private::Piece::len(&$value_coerced) };
+    // Counting the separator that `PathBuf::push` may insert before it.
+    (@len path $value_coerced:expr) => { $value_coerced.as_os_str().len() + 1 };
+    // Counting the `/`, `?`, `&` or `#` that may be inserted before it.
+    (@len url $value_coerced:expr) => { $value_coerced.len() + 1 };
+    (@len url_piece $value_coerced:expr) => { $crate::__private::Piece::len(&$value_coerced) + 1 };
+    (@len $kind:ident $value_coerced:expr) => { $value_coerced.len() };
+
+    (@into_boxed path $value:expr) => { $value.into_boxed_path() };
+    (@into_boxed path_raw $value:expr) => { $value.into_boxed_path() };
+    (@into_boxed os_str $value:expr) => { $value.into_boxed_os_str() };
+    (@into_boxed shell $value:expr) => { $value.into_boxed_os_str() };
+    (@into_boxed vec $value:expr) => { $value.into_boxed_slice() };
+    (@into_boxed $kind:ident $value:expr) => { $value.into_boxed_str() };
+
+    (@total_len path $input:ident) => { $input.as_os_str().len() };
+    (@total_len path_raw $input:ident) => { $input.as_os_str().len() };
+    (@total_len $kind:ident $input:ident) => { $input.len() };
+
+    (@is_empty piece $value_coerced:expr) => { $crate::__private::Piece::is_empty(&$value_coerced) };
+    (@is_empty $kind:ident $value_coerced:expr) => { $crate::__cat!(@len $kind $value_coerced) == 0 };
+
+    (@push str $input:ident $value_coerced:expr) => { $input.push_str($value_coerced) };
+    (@push path $input:ident $value_coerced:expr) => { $input.push($value_coerced) };
+    (@push os_str $input:ident $value_coerced:expr) => { $input.push(&$value_coerced) };
+    (@push vec $input:ident $value_coerced:expr) => { $input.extend_from_slice($value_coerced) };
+    (@push json $input:ident $value_coerced:expr) => { $input.push_str($value_coerced) };
+    (@push html $input:ident $value_coerced:expr) => { $input.push_str($value_coerced) };
+    (@push sql $input:ident $value_coerced:expr) => { $input.push_str($value_coerced) };
+    (@push csv $input:ident $value_coerced:expr) => { $input.push_str($value_coerced) };
+    (@push query $input:ident $value_coerced:expr) => { $input.push_str($value_coerced) };
+    (@push shell $input:ident $value_coerced:expr) => { $input.push(&$value_coerced) };
+    (@push env_paths $input:ident $value_coerced:expr) => { $input.push($value_coerced) };
+    (@push path_raw $input:ident $value_coerced:expr) => { $input.as_mut_os_string().push($value_coerced) };
+    (@push utf16 $input:ident $value_coerced:expr) => { $crate::__cat!(@push piece $input $value_coerced) };
+    (@push piece $input:ident $value_coerced:expr) => {
+        $crate::__private::Piece::emit(&$value_coerced, |s| $input.push_str(s))
+    };
+    // A piece pushed into an `OsString`.
+    (@push os_piece $input:ident $value_coerced:expr) => {
+        $crate::__private::Piece::emit(&$value_coerced, |s| $input.push(s))
+    };
+
+    (@emit piece $value_coerced:expr, $push_str:expr) => {
+        $crate::__private::Piece::emit(&$value_coerced, $push_str)
+    };
+    (@emit str $value_coerced:expr, $push_str:expr) => {
+        $crate::__private::Piece::emit($value_coerced, $push_str)
+    };
+
+    (@newlines piece $value_coerced:expr) => {{
+        let mut newlines = 0;
+        $crate::__private::Piece::emit(&$value_coerced, |s| newlines += $crate::__private::count_newlines(s));
+        newlines
+    }};
+    (@newlines str $value_coerced:expr) => { $crate::__private::count_newlines($value_coerced) };
+
+    // Measure and push a piece in join mode, where `csv` fields are quoted
+    // depending on the separator.
+    (@len_in csv $options:ident $value_coerced:expr) => {
+        $crate::__private::csv_field_len($value_coerced, $options.sep)
+    };
+    (@len_in $value_kind:ident $options:ident $value_coerced:expr) => {
+        $crate::__cat!(@len $value_kind $value_coerced)
+    };
+    (@push_in csv $input:ident $options:ident $value_coerced:expr) => {
+        $crate::__private::emit_csv_field($value_coerced, $options.sep, |s| $input.push_str(s))
+    };
+    (@push_in $value_kind:ident $input:ident $options:ident $value_coerced:expr) => {
+        $crate::__cat!(@push $value_kind $input $value_coerced)
+    };
+
+    // Add up lengths, optionally multiplied by a count, failing fast on
+    // overflow rather than reserving too little.
+    (@add $additional:ident $len:expr) => {
+        $additional = $crate::__private::add_len($additional, $len);
+    };
+    (@add $additional:ident $len:expr, $count:expr) => {
+        $additional = $crate::__private::add_len($additional, $crate::__private::mul_len($len, $count));
+    };
+
+    // `$value_kind` is `$kind` for plain values, or `piece` for values
+    // implementing `Piece`, which are only supported for `str` and the
+    // dialects of it like `json` and `html`.
+    //
+    // `$mode` is `()` when concatenating, or `(join options pieces pushed)`
+    // when joining, where `pieces` counts the pieces still to be pushed and
+    // `pushed` tells whether a separator is due before the next piece, and
+    // the optional `invalid` tells whether a piece contains the separator, or
+    // `(indent options at_line_start)` when indenting, where `at_line_start`
+    // tells whether the indent is due before the next non-empty line, or
+    // `(dedent state)` when dedenting, where `state` measures the common
+    // indentation while counting and strips it while pushing, or
+    // `(strict state)` when checking path components, where `state` tracks
+    // whether any of them would replace the path built so far, or
+    // `(normalize)` when normalizing path components, or `(inline fits)` when
+    // concatenating into an `InlineString`, where `fits` tells whether
+    // everything has fit so far, or `(url query)` when building a URL, where
+    // `query` tells where the query pairs go.
+    (@count $kind:ident $value_kind:ident $additional:ident () $value_coerced:ident) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced));
+    };
+    (@count $kind:ident $value_kind:ident $additional:ident () $value_coerced:ident * $count:ident) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced), $count);
+    };
+    (@count $kind:ident $value_kind:ident $additional:ident (join $options:ident $pieces:ident $pushed:ident $($invalid:ident)?) $value_coerced:ident) => {
+        $($invalid = $invalid || $crate::__private::contains_separator($value_coerced);)?
+        if !($options.skip_empty && $crate::__cat!(@is_empty $value_kind $value_coerced)) {
+            $crate::__cat!(@add $additional $crate::__cat!(@len_in $value_kind $options $value_coerced));
+            $crate::__cat!(@add $pieces 1);
+        }
+    };
+    (@count $kind:ident $value_kind:ident $additional:ident (join $options:ident $pieces:ident $pushed:ident $($invalid:ident)?) $value_coerced:ident * $count:ident) => {
+        $($invalid = $invalid || $crate::__private::contains_separator($value_coerced);)?
+        if !($options.skip_empty && $crate::__cat!(@is_empty $value_kind $value_coerced)) {
+            $crate::__cat!(@add $additional $crate::__cat!(@len_in $value_kind $options $value_coerced), $count);
+            $crate::__cat!(@add $pieces $count);
+        }
+    };
+
+    (@count $kind:ident $value_kind:ident $additional:ident (indent $options:ident $at_line_start:ident) $value_coerced:ident $(* $count:ident)?) => {
+        let indents = $crate::__private::mul_len(
+            $options.indent.len(),
+            $crate::__cat!(@newlines $value_kind $value_coerced),
+        );
+        $crate::__cat!(@add $additional $crate::__private::add_len(
+            $crate::__cat!(@len $value_kind $value_coerced),
+            indents,
+        ) $(, $count)?);
+    };
+
+    (@count $kind:ident $value_kind:ident $additional:ident (dedent $state:ident) $value_coerced:ident) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced));
+        $crate::__cat!(@emit $value_kind $value_coerced, |s| $state.measure(s));
+    };
+    (@count $kind:ident $value_kind:ident $additional:ident (dedent $state:ident) $value_coerced:ident * $count:ident) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced), $count);
+        for _ in 0..$count {
+            $crate::__cat!(@emit $value_kind $value_coerced, |s| $state.measure(s));
+        }
+    };
+
+    (@count $kind:ident $value_kind:ident $additional:ident (strict $state:ident) $value_coerced:ident) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced));
+        $state.check($value_coerced);
+    };
+    (@count $kind:ident $value_kind:ident $additional:ident (strict $state:ident) $value_coerced:ident * $count:ident) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced), $count);
+        for _ in 0..$count {
+            $state.check($value_coerced);
+        }
+    };
+
+    (@count $kind:ident $value_kind:ident $additional:ident (normalize) $value_coerced:ident $(* $count:ident)?) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced) $(, $count)?);
+    };
+
+    (@count $kind:ident $value_kind:ident $additional:ident (url $query:ident) $value_coerced:ident $(* $count:ident)?) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced) $(, $count)?);
+    };
+
+    (@count $kind:ident $value_kind:ident $additional:ident (inline $fits:ident) $value_coerced:ident $(* $count:ident)?) => {
+        $crate::__cat!(@add $additional $crate::__cat!(@len $value_kind $value_coerced) $(, $count)?);
+    };
+
+    (@put $kind:ident url $input:ident (url $query:ident) $value_coerced:ident) => {{
+        use $crate::__private::UrlString as _;
+        $input.push_url_segment(&mut $query, $value_coerced);
+    }};
+    (@put $kind:ident $value_kind:ident $input:ident () $value_coerced:ident) => {
+        $crate::__cat!(@push $value_kind $input $value_coerced);
+    };
+    (@put $kind:ident $value_kind:ident $input:ident (join $options:ident $pieces:ident $pushed:ident $($invalid:ident)?) $value_coerced:ident) => {
+        if !($options.skip_empty && $crate::__cat!(@is_empty $value_kind $value_coerced)) {
+            if $pushed {
+                match $options.last_sep {
+                    ::core::option::Option::Some(last_sep) if $pieces == 1 => {
+                        $crate::__cat!(@push $kind $input last_sep);
+                    }
+                    _ => $crate::__cat!(@push $kind $input $options.sep),
+                }
+            }
+            $pushed = true;
+            $pieces -= 1;
+            $crate::__cat!(@push_in $value_kind $input $options $value_coerced);
+            $crate::__cat!(@push $kind $input $options.terminator);
+        }
+    };
+
+    (@put $kind:ident $value_kind:ident $input:ident (indent $options:ident $at_line_start:ident) $value_coerced:ident) => {
+        $crate::__cat!(@emit $value_kind $value_coerced, |s| {
+            $crate::__private::push_indented(s, $options.indent, &mut $at_line_start, |s| $input.push_str(s))
+        });
+    };
+
+    (@put $kind:ident $value_kind:ident $input:ident (dedent $state:ident) $value_coerced:ident) => {
+        $crate::__cat!(@emit $value_kind $value_coerced, |s| $state.push(s, |s| $input.push_str(s)));
+    };
+
+    (@put $kind:ident $value_kind:ident $input:ident (normalize) $value_coerced:ident) => {
+        $crate::__private::push_normalized(&mut $input, $value_coerced);
+    };
+
+    // Pieces that were counted short, like streamed ones, may still not fit.
+    (@put $kind:ident $value_kind:ident $input:ident (inline $fits:ident) $value_coerced:ident) => {
+        $crate::__cat!(@emit $value_kind $value_coerced, |s| {
+            $fits = $fits && $input.try_push_str(s).is_ok();
+        });
+    };
+    (@put $kind:ident $value_kind:ident $input:ident (strict $state:ident) $value_coerced:ident) => {
+        $crate::__cat!(@push $value_kind $input $value_coerced);
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident (join $options:ident $pieces:ident $pushed:ident) [$($pushes:tt)*]) => {
+        // A separator is also due before the first piece if the target
+        // already has some.
+        let seps = $crate::__private::add_len($pieces, $pushed as usize).saturating_sub(1);
+        match $options.last_sep {
+            ::core::option::Option::Some(last_sep) if seps > 0 => {
+                $crate::__cat!(@add $additional $crate::__cat!(@len $kind $options.sep), seps - 1);
+                $crate::__cat!(@add $additional $crate::__cat!(@len $kind last_sep));
+            }
+            _ => {
+                $crate::__cat!(@add $additional $crate::__cat!(@len $kind $options.sep), seps);
+            }
+        }
+        $crate::__cat!(@add $additional $crate::__cat!(@len $kind $options.terminator), $pieces);
+        $crate::__cat!(@add $additional $crate::__cat!(@len $kind $options.prefix));
+        $crate::__cat!(@add $additional $crate::__cat!(@len $kind $options.suffix));
+        $crate::__cat!(@reserve $input $additional [
+            $crate::__cat!(@push $kind $input $options.prefix);
+            $($pushes)*
+            $crate::__cat!(@push $kind $input $options.suffix);
+        ]);
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident (join $options:ident $pieces:ident $pushed:ident $invalid:ident) [$($pushes:tt)*]) => {
+        if !$invalid {
+            $crate::__cat!(@munch $kind $input $additional (join $options $pieces $pushed) [$($pushes)*]);
+        }
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident (dedent $state:ident) [$($pushes:tt)*]) => {
+        // Saturating, since pieces counted short, like streamed ones, may
+        // have more indentation stripped than was counted.
+        $additional = $additional.saturating_sub($state.start_pushing());
+        $crate::__cat!(@reserve $input $additional [$($pushes)*]);
+    };
+    (@munch $kind:ident $input:ident $additional:ident (strict $state:ident) [$($pushes:tt)*]) => {
+        if $state.result().is_ok() {
+            $crate::__cat!(@reserve $input $additional [$($pushes)*]);
+        }
+    };
+    (@munch $kind:ident $input:ident $additional:ident (inline $fits:ident) [$($pushes:tt)*]) => {
+        $fits = $additional <= $input.remaining_capacity();
+        if $fits {
+            $($pushes)*
+        }
+    };
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*]) => {
+        $crate::__cat!(@reserve $input $additional [$($pushes)*]);
+    };
+
+    // Reserve the counted length once and push everything. A wrapped
+    // `String` is made mutable by the reserve, such as a shared one made
+    // unique, and stays so while pushing.
+    (@reserve $input:ident $additional:ident [$($pushes:tt)*]) => {
+        #[allow(unused_imports)]
+        use $crate::__private::StringTarget as _;
         $input.reserve($additional);
-        $($input.extend_from_slice($values_coerced);)*
+        let reserved = $crate::__private::ReservedCapacity::new($input.capacity());
+        $($pushes)*
+        reserved.check($input.capacity());
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @when(let $pat:pat = $scrutinee:expr, $value:expr) $(, $($tail:tt)*)?) => {
+        match &$scrutinee {
+            scrutinee => {
+                let value = if let $pat = scrutinee {
+                    ::core::option::Option::Some(&*$value)
+                } else {
+                    ::core::option::Option::None
+                };
+                $crate::__cat!(@munch_option $kind $input $additional $mode [$($pushes)*] value; $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @when($cond:expr, $value:expr) $(, $($tail:tt)*)?) => {
+        let value = if $cond {
+            ::core::option::Option::Some(&*$value)
+        } else {
+            ::core::option::Option::None
+        };
+        $crate::__cat!(@munch_option $kind $input $additional $mode [$($pushes)*] value; $($($tail)*)?);
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @all($values:expr) $(, $($tail:tt)*)?) => {
+        match &$values {
+            values => {
+                for value in values {
+                    let value_coerced = $crate::__cat!(@coerce $kind value);
+                    $crate::__cat!(@count $kind $kind $additional $mode value_coerced);
+                }
+                $crate::__cat!(@munch $kind $input $additional $mode [$($pushes)* {
+                    for value in values {
+                        let value_coerced = $crate::__cat!(@coerce $kind value);
+                        $crate::__cat!(@put $kind $kind $input $mode value_coerced);
+                    }
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @iter($values:expr) $(, $($tail:tt)*)?) => {
+        match ::core::iter::IntoIterator::into_iter($values) {
+            values => {
+                for value in ::core::clone::Clone::clone(&values) {
+                    let value = &value;
+                    let value_coerced = $crate::__cat!(@coerce $kind value);
+                    $crate::__cat!(@count $kind $kind $additional $mode value_coerced);
+                }
+                $crate::__cat!(@munch $kind $input $additional $mode [$($pushes)* {
+                    for value in values {
+                        let value = &value;
+                        let value_coerced = $crate::__cat!(@coerce $kind value);
+                        $crate::__cat!(@put $kind $kind $input $mode value_coerced);
+                    }
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @repeat($value:expr, $count:expr) $(, $($tail:tt)*)?) => {
+        match (&$value, $count) {
+            (value, count) => {
+                let count: usize = count;
+                let value_coerced = $crate::__cat!(@coerce $kind value);
+                $crate::__cat!(@count $kind $kind $additional $mode value_coerced * count);
+                $crate::__cat!(@munch $kind $input $additional $mode [$($pushes)* {
+                    for _ in 0..count {
+                        $crate::__cat!(@put $kind $kind $input $mode value_coerced);
+                    }
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad($($args:tt)*) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @pad[chars]($($args)*) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad_cols($($args:tt)*) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @pad[cols]($($args)*) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad_ansi($($args:tt)*) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @pad[ansi]($($args)*) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad_ansi_cols($($args:tt)*) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @pad[ansi_cols]($($args)*) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad[$measure:ident]($value:expr, $fill:literal < $width:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @padded($value, $fill, Left, $width, $measure) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad[$measure:ident]($value:expr, $fill:literal > $width:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @padded($value, $fill, Right, $width, $measure) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad[$measure:ident]($value:expr, $fill:literal ^ $width:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @padded($value, $fill, Center, $width, $measure) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad[$measure:ident]($value:expr, < $width:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @padded($value, ' ', Left, $width, $measure) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad[$measure:ident]($value:expr, > $width:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @padded($value, ' ', Right, $width, $measure) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad[$measure:ident]($value:expr, ^ $width:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @padded($value, ' ', Center, $width, $measure) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pad[$measure:ident]($value:expr, $width:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @padded($value, ' ', Left, $width, $measure) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @padded($value:expr, $fill:expr, $align:ident, $width:expr, $measure:ident) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__cat!(@coerce str value);
+                let value_coerced = $crate::__private::Pad::new(
+                    value_coerced,
+                    $fill,
+                    $crate::__private::Align::$align,
+                    $width,
+                    $crate::__private::measure::$measure(value_coerced),
+                );
+                $crate::__cat!(@count str piece $additional $mode value_coerced);
+                $crate::__cat!(@munch str $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put str piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch json $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @esc($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch json $input $additional $mode [$($pushes)*] @piece[$crate::__private::JsonEscaped::new]($value) $(, $($tail)*)?);
+    };
+
+    (@munch html $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @esc($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch html $input $additional $mode [$($pushes)*] @piece[$crate::__private::HtmlEscaped::new]($value) $(, $($tail)*)?);
+    };
+
+    (@munch sql $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @ident($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch sql $input $additional $mode [$($pushes)*] @piece[$crate::__private::SqlQuoted::new]($value, '"') $(, $($tail)*)?);
+    };
+    (@munch sql $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @ident($value:expr, $quote:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch sql $input $additional $mode [$($pushes)*] @piece[$crate::__private::SqlQuoted::new]($value, $quote) $(, $($tail)*)?);
+    };
+    (@munch sql $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @lit($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch sql $input $additional $mode [$($pushes)*] @piece[$crate::__private::SqlQuoted::new]($value, '\'') $(, $($tail)*)?);
+    };
+
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @lossy($value:expr) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__private::as_os_str(value);
+                let value_coerced = $crate::__private::Lossy::new(value_coerced);
+                $crate::__cat!(@count str piece $additional $mode value_coerced);
+                $crate::__cat!(@munch str $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put str piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @display($value:expr) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__private::Displayed::new(value);
+                $crate::__cat!(@count str piece $additional $mode value_coerced);
+                $crate::__cat!(@munch str $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put str piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @dbg($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] format_args!("{:?}", $value) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] format_args!($($args:tt)*) $(, $($tail:tt)*)?) => {
+        match &::core::format_args!($($args)*) {
+            value => {
+                let value_coerced = $crate::__private::Streamed::new(value);
+                $crate::__cat!(@count str piece $additional $mode value_coerced);
+                $crate::__cat!(@munch str $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put str piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @chars($chars:expr) $(, $($tail:tt)*)?) => {
+        match $crate::__private::Chars::new(::core::iter::IntoIterator::into_iter($chars)) {
+            value_coerced => {
+                $crate::__cat!(@count str piece $additional $mode value_coerced);
+                $crate::__cat!(@munch str $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put str piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @strict($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] ::core::str::from_utf8(
+            ::core::convert::AsRef::<::std::ffi::OsStr>::as_ref(&$value).as_encoded_bytes()
+        )? $(, $($tail)*)?);
+    };
+
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @trim($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] $crate::__private::as_str(&$value).trim() $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @trim_start($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] $crate::__private::as_str(&$value).trim_start() $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @trim_end($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] $crate::__private::as_str(&$value).trim_end() $(, $($tail)*)?);
+    };
+
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @upper($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @piece[$crate::__private::CaseMapped::upper]($value) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @lower($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @piece[$crate::__private::CaseMapped::lower]($value) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @re_esc($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @piece[$crate::__private::RegexEscaped::new]($value) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @replace($value:expr, $from:expr, $to:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @piece[$crate::__private::Replaced::new]($value, $from, $to) $(, $($tail)*)?);
+    };
+
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @hex($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @bytes[$crate::__private::Hex::new]($value) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @b64($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @bytes[$crate::__private::Base64::new]($value) $(, $($tail)*)?);
+    };
+    // Bytes wrapped by `$ctor` into a `Piece`.
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @bytes[$($ctor:tt)*]($value:expr) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $($ctor)*(::core::convert::AsRef::<[u8]>::as_ref(value));
+                $crate::__cat!(@count str piece $additional $mode value_coerced);
+                $crate::__cat!(@munch str $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put str piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @enc($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @piece[$crate::__private::PercentEncoded::new]($value, $crate::percent::COMPONENT) $(, $($tail)*)?);
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @enc($value:expr, $set:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch str $input $additional $mode [$($pushes)*] @piece[$crate::__private::PercentEncoded::new]($value, $set) $(, $($tail)*)?);
+    };
+
+    (@munch query $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $key:ident = $value:expr $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch query $input $additional $mode [$($pushes)*] @pair(::core::stringify!($key), $value) $(, $($tail)*)?);
+    };
+    (@munch query $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $key:literal = $value:expr $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch query $input $additional $mode [$($pushes)*] @pair($key, $value) $(, $($tail)*)?);
+    };
+    (@munch query $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @pair($key:expr, $value:expr) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__private::QueryPair::new(
+                    $key,
+                    $crate::__private::QueryValue::query_value(value),
+                );
+                $crate::__cat!(@count query piece $additional $mode value_coerced);
+                $crate::__cat!(@munch query $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put query piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @enc($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch url $input $additional $mode [$($pushes)*] @url[push_url_segment_piece, $crate::__private::PercentEncoded::new]($value, $crate::percent::COMPONENT) $(, $($tail)*)?);
+    };
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @enc($value:expr, $set:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch url $input $additional $mode [$($pushes)*] @url[push_url_segment_piece, $crate::__private::PercentEncoded::new]($value, $set) $(, $($tail)*)?);
+    };
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $key:ident = $value:expr $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch url $input $additional $mode [$($pushes)*] @url[push_url_query_pair, $crate::__private::QueryPair::new](::core::stringify!($key), @query_value $value) $(, $($tail)*)?);
+    };
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $key:literal = $value:expr $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch url $input $additional $mode [$($pushes)*] @url[push_url_query_pair, $crate::__private::QueryPair::new]($key, @query_value $value) $(, $($tail)*)?);
+    };
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @fragment($value:expr) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch url $input $additional $mode [$($pushes)*] @url[push_url_fragment, ::core::convert::identity]($value) $(, $($tail)*)?);
+    };
+    // A piece pushed with one of the `UrlString` methods, counted along with
+    // the `/`, `?`, `&` or `#` that may be inserted before it.
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @url[$push:ident, $($ctor:tt)*]($key:expr, @query_value $value:expr) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $($ctor)*($key, $crate::__private::QueryValue::query_value(value));
+                $crate::__cat!(@munch url $input $additional $mode [$($pushes)*] @url_put[$push](value_coerced) $(, $($tail)*)?);
+            }
+        }
+    };
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @url[$push:ident, $($ctor:tt)*]($value:expr $(, $arg:expr)*) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__cat!(@coerce str value);
+                let value_coerced = $($ctor)*(value_coerced $(, $arg)*);
+                $crate::__cat!(@munch url $input $additional $mode [$($pushes)*] @url_put[$push](value_coerced) $(, $($tail)*)?);
+            }
+        }
+    };
+    (@munch url $input:ident $additional:ident (url $query:ident) [$($pushes:tt)*] @url_put[$push:ident]($value_coerced:ident) $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@count url url_piece $additional (url $query) $value_coerced);
+        $crate::__cat!(@munch url $input $additional (url $query) [$($pushes)* {
+            use $crate::__private::UrlString as _;
+            $input.$push(&mut $query, &$value_coerced);
+        }] $($($tail)*)?);
+    };
+
+    (@munch utf16 $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @strict($value:expr) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__private::Utf16::strict(::core::convert::AsRef::<[u16]>::as_ref(value))?;
+                $crate::__cat!(@count utf16 piece $additional $mode value_coerced);
+                $crate::__cat!(@munch utf16 $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put utf16 piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch shell $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @quote($value:expr) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__cat!(@coerce str value);
+                let value_coerced = $crate::__private::ShellQuoted::new(value_coerced);
+                $crate::__cat!(@count shell os_piece $additional $mode value_coerced);
+                $crate::__cat!(@munch shell $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put shell os_piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    // The directory in front of a file name, which is pushed as a component
+    // along with a trailing separator.
+    (@munch path_raw $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @dir($dir:expr) $(, $($tail:tt)*)?) => {
+        match &$dir {
+            value => {
+                let value_coerced = $crate::__cat!(@coerce path value);
+                $crate::__cat!(@count path path $additional $mode value_coerced);
+                $crate::__cat!(@munch path_raw $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put path path $input $mode value_coerced);
+                    $input.push("");
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    // A value coerced to `&str` and then wrapped by `$ctor`, along with the
+    // extra arguments, into a `Piece`.
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] @piece[$($ctor:tt)*]($value:expr $(, $arg:expr)*) $(, $($tail:tt)*)?) => {
+        match &$value {
+            value => {
+                let value_coerced = $crate::__cat!(@coerce str value);
+                let value_coerced = $($ctor)*(value_coerced $(, $arg)*);
+                $crate::__cat!(@count $kind piece $additional $mode value_coerced);
+                $crate::__cat!(@munch $kind $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put $kind piece $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
     };
 
-    (@stack $input:ident, $additional:ident; $($values_coerced:ident)*; $head:expr, $($tail:expr,)*) => {
+    // Plain elements are taken four at a time to keep the recursion shallow,
+    // except where one of them could still be special: `key = value` pairs
+    // parse as assignments, and `format_args!` would lose its streaming.
+    (@munch url $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $head:expr $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch_one url $input $additional $mode [$($pushes)*] $head $(, $($tail)*)?)
+    };
+    (@munch query $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $head:expr $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch_one query $input $additional $mode [$($pushes)*] $head $(, $($tail)*)?)
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $v0:expr, format_args! $($tail:tt)*) => {
+        $crate::__cat!(@munch_one str $input $additional $mode [$($pushes)*] $v0, format_args! $($tail)*)
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $v0:expr, $v1:expr, format_args! $($tail:tt)*) => {
+        $crate::__cat!(@munch_one str $input $additional $mode [$($pushes)*] $v0, $v1, format_args! $($tail)*)
+    };
+    (@munch str $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $v0:expr, $v1:expr, $v2:expr, format_args! $($tail:tt)*) => {
+        $crate::__cat!(@munch_one str $input $additional $mode [$($pushes)*] $v0, $v1, $v2, format_args! $($tail)*)
+    };
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $v0:expr, $v1:expr, $v2:expr, $v3:expr $(, $($tail:tt)*)?) => {
+        match (&$v0, &$v1, &$v2, &$v3) {
+            (v0, v1, v2, v3) => {
+                let v0 = $crate::__cat!(@coerce $kind v0);
+                $crate::__cat!(@count $kind $kind $additional $mode v0);
+                let v1 = $crate::__cat!(@coerce $kind v1);
+                $crate::__cat!(@count $kind $kind $additional $mode v1);
+                let v2 = $crate::__cat!(@coerce $kind v2);
+                $crate::__cat!(@count $kind $kind $additional $mode v2);
+                let v3 = $crate::__cat!(@coerce $kind v3);
+                $crate::__cat!(@count $kind $kind $additional $mode v3);
+                $crate::__cat!(@munch $kind $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put $kind $kind $input $mode v0);
+                    $crate::__cat!(@put $kind $kind $input $mode v1);
+                    $crate::__cat!(@put $kind $kind $input $mode v2);
+                    $crate::__cat!(@put $kind $kind $input $mode v3);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+    (@munch $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $head:expr $(, $($tail:tt)*)?) => {
+        $crate::__cat!(@munch_one $kind $input $additional $mode [$($pushes)*] $head $(, $($tail)*)?)
+    };
+    (@munch_one $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $head:expr $(, $($tail:tt)*)?) => {
         match &$head {
             value => {
-                let value_coerced = ::core::convert::AsRef::<[_]>::as_ref(&value);
-                $additional += value_coerced.len();
-                $crate::vec_cat!(@stack $input, $additional; $($values_coerced)* value_coerced; $($tail,)*);
+                let value_coerced = $crate::__cat!(@coerce $kind value);
+                $crate::__cat!(@count $kind $kind $additional $mode value_coerced);
+                $crate::__cat!(@munch $kind $input $additional $mode [$($pushes)* {
+                    $crate::__cat!(@put $kind $kind $input $mode value_coerced);
+                }] $($($tail)*)?);
+            }
+        }
+    };
+
+    (@munch_option $kind:ident $input:ident $additional:ident $mode:tt [$($pushes:tt)*] $value:ident; $($tail:tt)*) => {
+        let value_coerced = $value.map(|value| $crate::__cat!(@coerce $kind value));
+        if let ::core::option::Option::Some(value_coerced) = &value_coerced {
+            $crate::__cat!(@count $kind $kind $additional $mode value_coerced);
+        }
+        $crate::__cat!(@munch $kind $input $additional $mode [$($pushes)* {
+            if let ::core::option::Option::Some(value_coerced) = value_coerced {
+                $crate::__cat!(@put $kind $kind $input $mode value_coerced);
+            }
+        }] $($tail)*);
+    };
+
+    (@target $kind:ident cat [] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind cat [$crate::__cat!(@new $kind)] [] $($el)*)
+    };
+
+    // `file_name_cat` takes an optional directory in front of the first `;`,
+    // and an optional extension after the second one, so the elements are
+    // scanned again after a directory.
+    (@target file_name file_name [] $($el:tt)*) => {
+        $crate::__cat!(@target path_raw cat [] $($el)*)
+    };
+    (@target file_name file_name [$($el:tt)+] ext = $ext:expr) => {
+        $crate::__cat!(@target path_raw cat [] $($el)+, ".", $ext)
+    };
+    (@target file_name file_name [$($dir:tt)+] $($el:tt)*) => {
+        $crate::__cat!(@split file_name file_name_ext [($($dir)+)] $($el)*)
+    };
+    (@target file_name file_name_ext [($($dir:tt)+) $($el:tt)+] ext = $ext:expr) => {
+        $crate::__cat!(@target path_raw cat [] @dir($($dir)+), $($el)+, ".", $ext)
+    };
+    (@target file_name file_name_ext [] ($($dir:tt)+) $($el:tt)*) => {
+        $crate::__cat!(@target path_raw cat [] @dir($($dir)+), $($el)*)
+    };
+
+    // Sort the header into the target and the `key = value` options, which
+    // are then used as the fields of the options struct of the mode.
+    (@target $kind:ident $mode:ident [$($header:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [] [] [$($header)*] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [@count $($input:tt)*] [$($options:tt)*] [] $($el:tt)*) => {
+        $crate::__cat!(@counted $kind $mode [$($input)*] [$($options)*] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [@boxed] [$($options:tt)*] [] $($el:tt)*) => {
+        $crate::__cat!(@into_boxed $kind $crate::__cat!(@with_options $kind $mode [] [$($options)*] $($el)*))
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind $mode [$($input)*] [$($options)*] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [] [$($options:tt)*] [boxed = true $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [@boxed] [$($options)*] [$($($rest)*)?] $($el)*)
+    };
+    (@header path cat [$($input:tt)*] [$($options:tt)*] [normalize = true $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header path normalize [$($input)*] [$($options)*] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident strict [$($input:tt)*] [$($options:tt)*] [count = true $(, $($rest:tt)*)?] $($el:tt)*) => {
+        ::core::compile_error!("`count = true` is not supported by a macro that returns a `Result`")
+    };
+    (@header $kind:ident env_paths [$($input:tt)*] [$($options:tt)*] [count = true $(, $($rest:tt)*)?] $($el:tt)*) => {
+        ::core::compile_error!("`count = true` is not supported by a macro that returns a `Result`")
+    };
+    (@header $kind:ident inline [$($input:tt)*] [$($options:tt)*] [count = true $(, $($rest:tt)*)?] $($el:tt)*) => {
+        ::core::compile_error!("`count = true` is not supported by a macro that returns a `Result`")
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [count = false $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)*] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [count = true $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [@count $($input)*] [$($options)*] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [crlf = true $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* terminator: "\r\n",] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [sep = $sep:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* sep: &$sep,] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [last_sep = $last_sep:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* last_sep: ::core::option::Option::Some(&$last_sep),] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [prefix = $prefix:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* prefix: &$prefix,] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [suffix = $suffix:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* suffix: &$suffix,] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [terminator = $terminator:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* terminator: &$terminator,] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [indent = $indent:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* indent: &$indent,] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [$($input:tt)*] [$($options:tt)*] [$key:ident = $value:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$($input)*] [$($options)* $key: $value,] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [] [] [$input:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [$input] [] [$($($rest)*)?] $($el)*)
+    };
+    (@header $kind:ident $mode:ident [@count] [] [$input:expr $(, $($rest:tt)*)?] $($el:tt)*) => {
+        $crate::__cat!(@header $kind $mode [@count $input] [] [$($($rest)*)?] $($el)*)
+    };
+
+    // Run the mode on the target, and return how much it grew instead.
+    (@counted $kind:ident $mode:ident [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@counted $kind $mode [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@counted $kind:ident $mode:ident [$($input:tt)+] [$($options:tt)*] $($el:tt)*) => {{
+        let input = $($input)+;
+        let len = $crate::__cat!(@total_len $kind input);
+        let input = $crate::__cat!(@with_options $kind $mode [input] [$($options)*] $($el)*);
+        $crate::__cat!(@total_len $kind input).saturating_sub(len)
+    }};
+
+    (@with_options $kind:ident cat [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind cat [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@with_options $kind:ident cat [$($input:tt)+] [] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(unused_mut)]
+        let mut additional: usize = 0;
+        $crate::__cat!(@munch $kind input additional () [] $($el)*);
+        input
+    }};
+    (@with_options $kind:ident cat [$($input:tt)+] [$($options:tt)+] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(clippy::needless_update)]
+        let options = $crate::__private::CatOptions {
+            $($options)*
+            ..::core::default::Default::default()
+        };
+        let mut additional = options.reserve_extra;
+        $crate::__cat!(@munch $kind input additional () [] $($el)*);
+        if options.fit {
+            input.shrink_to_fit();
+        }
+        input
+    }};
+
+    (@with_options $kind:ident join [$($input:tt)*] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@join $kind [$crate::__cat!(@join_defaults $kind)] [$($input)*] [$($options)*] $($el)*)
+    };
+    // Joining with a terminator after every piece.
+    (@with_options $kind:ident lines [$($input:tt)*] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@join $kind [$crate::__private::JoinOptions::lines()] [$($input)*] [$($options)*] $($el)*)
+    };
+    (@join $kind:ident [$($defaults:tt)+] [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@join $kind [$($defaults)+] [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@join $kind:ident [$($defaults:tt)+] [$($input:tt)+] [$($options:tt)*] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(clippy::needless_update)]
+        let options: $crate::__private::JoinOptions<$crate::__cat!(@unsized $kind)> = $crate::__private::JoinOptions {
+            $($options)*
+            ..$($defaults)+
+        };
+        let mut additional = options.reserve_extra;
+        #[allow(unused_mut)]
+        let mut pieces = 0_usize;
+        #[allow(unused_mut)]
+        let mut pushed = false;
+        $crate::__cat!(@munch $kind input additional (join options pieces pushed) [] $($el)*);
+        if options.fit {
+            input.shrink_to_fit();
+        }
+        input
+    }};
+
+    // Joining like `join`, but continuing after the entries already in the
+    // target, and failing without pushing anything if an entry contains the
+    // separator.
+    (@with_options $kind:ident env_paths [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind env_paths [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@with_options $kind:ident env_paths [$($input:tt)+] [$($options:tt)*] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(clippy::needless_update)]
+        let options: $crate::__private::JoinOptions<$crate::__cat!(@unsized $kind)> = $crate::__private::JoinOptions {
+            $($options)*
+            ..$crate::__cat!(@join_defaults $kind)
+        };
+        let mut additional = options.reserve_extra;
+        #[allow(unused_mut)]
+        let mut pushed = !input.is_empty();
+        #[allow(unused_mut)]
+        let mut pieces = 0_usize;
+        #[allow(unused_mut)]
+        let mut invalid = false;
+        $crate::__cat!(@munch $kind input additional (join options pieces pushed invalid) [] $($el)*);
+        if invalid {
+            ::core::result::Result::Err($crate::JoinPathsError)
+        } else {
+            if options.fit {
+                input.shrink_to_fit();
             }
+            ::core::result::Result::Ok(input)
+        }
+    }};
+
+    (@with_options $kind:ident indent [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind indent [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@with_options $kind:ident indent [$($input:tt)+] [$($options:tt)*] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(clippy::needless_update)]
+        let options = $crate::__private::IndentOptions {
+            $($options)*
+            ..::core::default::Default::default()
+        };
+        let mut additional = options.reserve_extra;
+        #[allow(unused_mut)]
+        let mut at_line_start = input.is_empty() || input.ends_with('\n');
+        if at_line_start {
+            additional += options.indent.len();
+        }
+        $crate::__cat!(@munch $kind input additional (indent options at_line_start) [] $($el)*);
+        if options.fit {
+            input.shrink_to_fit();
+        }
+        input
+    }};
+
+    (@with_options $kind:ident dedent [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind dedent [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@with_options $kind:ident dedent [$($input:tt)+] [$($options:tt)*] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(clippy::needless_update)]
+        let options = $crate::__private::CatOptions {
+            $($options)*
+            ..::core::default::Default::default()
+        };
+        let mut additional = options.reserve_extra;
+        let mut state = $crate::__private::Dedent::new();
+        $crate::__cat!(@munch $kind input additional (dedent state) [] $($el)*);
+        if options.fit {
+            input.shrink_to_fit();
         }
+        input
+    }};
+
+    (@with_options $kind:ident normalize [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind normalize [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@with_options $kind:ident normalize [$($input:tt)+] [$($options:tt)*] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(clippy::needless_update)]
+        let options = $crate::__private::CatOptions {
+            $($options)*
+            ..::core::default::Default::default()
+        };
+        let mut additional = options.reserve_extra;
+        $crate::__cat!(@munch $kind input additional (normalize) [] $($el)*);
+        if options.fit {
+            input.shrink_to_fit();
+        }
+        input
+    }};
+
+    (@with_options $kind:ident strict [] [] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind strict [$crate::__cat!(@new $kind)] [] $($el)*)
     };
+    (@with_options $kind:ident strict [$($input:tt)+] [] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        #[allow(unused_mut)]
+        let mut additional: usize = 0;
+        #[allow(unused_mut)]
+        let mut state = $crate::__private::StrictPath::new(&input);
+        $crate::__cat!(@munch $kind input additional (strict state) [] $($el)*);
+        state.result().map(|()| input)
+    }};
 
-    ($input:expr; $($el:expr),+ $(,)?) => {{
+    // Building a URL, where `query` tells where the query pairs go.
+    (@with_options $kind:ident url [] [$($options:tt)*] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind url [$crate::__cat!(@new $kind)] [$($options)*] $($el)*)
+    };
+    (@with_options $kind:ident url [$($input:tt)+] [$($options:tt)*] $($el:tt)*) => {{
         #[allow(unused_mut)]
-        let mut input = $input;
-        let mut additional = 0;
-        $crate::vec_cat!(@stack input, additional; ; $($el,)*);
+        let mut input = $($input)+;
+        #[allow(clippy::needless_update)]
+        let options = $crate::__private::CatOptions {
+            $($options)*
+            ..::core::default::Default::default()
+        };
+        #[allow(unused_mut)]
+        let mut additional = options.reserve_extra;
+        #[allow(unused_mut, unused_variables)]
+        let mut query = $crate::__private::UrlQuery::new(&input);
+        $crate::__cat!(@munch $kind input additional (url query) [] $($el)*);
+        if options.fit {
+            input.shrink_to_fit();
+        }
         input
     }};
 
-    ($($el:expr),+ $(,)?) => {
-        $crate::vec_cat!(::std::vec![]; $($el,)*)
+    // Concatenating into an `InlineString`, failing without pushing anything
+    // if the counted length does not fit, or undoing the pushes if pieces
+    // counted short turn out not to fit.
+    (@with_options $kind:ident inline [] [] $($el:tt)*) => {
+        $crate::__cat!(@with_options $kind inline [$crate::InlineString::new()] [] $($el)*)
+    };
+    (@with_options $kind:ident inline [$($input:tt)+] [] $($el:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut input = $($input)+;
+        let len = input.len();
+        #[allow(unused_mut)]
+        let mut additional: usize = 0;
+        #[allow(unused_mut, unused_assignments)]
+        let mut fits = true;
+        $crate::__cat!(@munch $kind input additional (inline fits) [] $($el)*);
+        if fits {
+            ::core::result::Result::Ok(input)
+        } else {
+            input.truncate(len);
+            ::core::result::Result::Err($crate::CapacityError)
+        }
+    }};
+
+    // Scan for the `;` a few tokens at a time to keep the recursion shallow.
+    (@split $kind:ident $mode:ident [$($header:tt)*] ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)*] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)* $t0] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt $t1:tt ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)* $t0 $t1] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt $t1:tt $t2:tt ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)* $t0 $t1 $t2] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)* $t0 $t1 $t2 $t3] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)* $t0 $t1 $t2 $t3 $t4] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)* $t0 $t1 $t2 $t3 $t4 $t5] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt ; $($el:tt)*) => {
+        $crate::__cat!(@target $kind $mode [$($header)* $t0 $t1 $t2 $t3 $t4 $t5 $t6] $($el)*)
+    };
+    (@split $kind:ident $mode:ident [$($header:tt)*] $t0:tt $t1:tt $t2:tt $t3:tt $t4:tt $t5:tt $t6:tt $t7:tt $($rest:tt)*) => {
+        $crate::__cat!(@split $kind $mode [$($header)* $t0 $t1 $t2 $t3 $t4 $t5 $t6 $t7] $($rest)*)
+    };
+    // No `;` at all, so everything scanned so far are elements.
+    (@split $kind:ident $mode:ident [$($el:tt)*] $($rest:tt)*) => {
+        $crate::__cat!(@target $kind $mode [] $($el)* $($rest)*)
+    };
+
+    ($kind:ident $mode:ident; $($tt:tt)*) => {
+        $crate::__cat!(@split $kind $mode [] $($tt)*)
     };
 }
 
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::case::CaseMapped;
+    pub use crate::cat::CatOptions;
+    pub use crate::coerce::{as_os_str, as_path, as_slice, as_str, to_os_str};
+    pub use crate::csv::{csv_field_len, emit_csv_field};
+    pub use crate::dedent::Dedent;
+    #[cfg(feature = "base64")]
+    pub use crate::encode::Base64;
+    #[cfg(feature = "hex")]
+    pub use crate::encode::Hex;
+    pub use crate::env_paths::contains_separator;
+    #[cfg(feature = "html")]
+    pub use crate::escape::HtmlEscaped;
+    pub use crate::escape::{JsonEscaped, RegexEscaped};
+    pub use crate::indent::{count_newlines, push_indented, IndentOptions};
+    pub use crate::join::JoinOptions;
+    pub use crate::len::{add_len, mul_len, ReservedCapacity};
+    pub use crate::normalize_path::push_normalized;
+    #[cfg(feature = "percent-encoding")]
+    pub use crate::percent_encoded::{PercentEncoded, QueryPair, QueryValue};
+    pub use crate::piece::{measure, Align, Chars, Displayed, Lossy, Pad, Piece, Streamed};
+    pub use crate::replace::Replaced;
+    pub use crate::shell::ShellQuoted;
+    pub use crate::sql::SqlQuoted;
+    pub use crate::strict_path::StrictPath;
+    pub use crate::target::StringTarget;
+    #[cfg(feature = "percent-encoding")]
+    pub use crate::url::{UrlQuery, UrlString};
+    pub use crate::utf16::Utf16;
+    #[cfg(feature = "fmt")]
+    pub use str_cat_macros::cat_fmt;
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+    use std::ffi::OsStr;
+    use std::path::{Path, PathBuf};
+
+    use crate::InlineString;
+
     #[test]
     fn currently_doc_tests_covered_everything() {}
+
+    #[test]
+    fn no_elements() {
+        assert_eq!(crate::str_cat!(), "");
+        let s: String = crate::cat!();
+        assert_eq!(s, "");
+        assert_eq!(crate::scratch_cat!(), "");
+        assert_eq!(crate::leak_cat!(), "");
+        assert_eq!(crate::static_cat!(), "");
+        assert_eq!(crate::intern_cat!(&mut HashSet::new();), "");
+        assert_eq!(crate::pieces!(), [] as [&str; 0]);
+        assert_eq!(crate::chain_cat!(), "");
+        assert_eq!(crate::path_cat!(), PathBuf::new());
+        assert_eq!(crate::utf16_cat!(), "");
+        let s: InlineString<4> = crate::inline_str_cat!().unwrap();
+        assert!(s.is_empty());
+        assert_eq!(crate::os_str_cat!(), OsStr::new(""));
+        let v: Vec<u8> = crate::vec_cat!();
+        assert!(v.is_empty());
+        assert_eq!(crate::str_join!(sep = ", ";), "");
+        assert_eq!(crate::lines_cat!(), "");
+        assert_eq!(crate::indent_cat!(), "");
+        assert_eq!(crate::dedent_cat!(), "");
+        assert_eq!(crate::json_str_cat!(), "");
+        assert_eq!(crate::sql_cat!(), "");
+        // No fields, which is a record of one empty field.
+        assert_eq!(crate::csv_cat!(), "\r\n");
+        assert_eq!(crate::path_append!(), PathBuf::new());
+        assert_eq!(crate::file_name_cat!(), Path::new(""));
+        assert_eq!(crate::strict_path_cat!().unwrap(), PathBuf::new());
+        assert_eq!(crate::env_paths_cat!().unwrap(), "");
+        assert_eq!(crate::shell_cat!(), "");
+        #[cfg(feature = "html")]
+        assert_eq!(crate::html_cat!(), "");
+        #[cfg(feature = "percent-encoding")]
+        {
+            assert_eq!(crate::query_cat!(), "");
+            assert_eq!(crate::url_cat!(), "");
+        }
+    }
 }
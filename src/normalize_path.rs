@@ -0,0 +1,23 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Push the components of `fragment` onto `path`, dropping `.` and resolving
+/// `..` against the last component where it is a normal one.
+///
+/// This is purely lexical, so `..` after a symlink may point somewhere else
+/// than the result. A `..` right after the root is dropped, and one with
+/// nothing to resolve against is kept.
+pub fn push_normalized(path: &mut PathBuf, fragment: &Path) {
+    for component in fragment.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match path.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    path.pop();
+                }
+                Some(Component::RootDir) => {}
+                _ => path.push(".."),
+            },
+            _ => path.push(component),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::len::add_len;
+
+/// Concatenate owned strings, reusing the buffer with the most spare capacity
+/// for the result.
+///
+/// The other strings are appended to that buffer, or prepended by rotating it
+/// in place, so the only allocation is a single grow of the reused buffer if
+/// it is still too small.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::concat_owned;
+///
+/// let mut body = String::with_capacity(64);
+/// body.push_str("world");
+/// let ptr = body.as_ptr();
+///
+/// let s = concat_owned(vec!["Hello, ".to_owned(), body, "!".to_owned()]);
+/// assert_eq!(s, "Hello, world!");
+/// assert_eq!(s.as_ptr(), ptr);
+/// ```
+pub fn concat_owned(mut strings: Vec<String>) -> String {
+    // Reversed so that the first one wins a tie, which needs no rotation.
+    let Some(index) = (0..strings.len())
+        .rev()
+        .max_by_key(|&i| strings[i].capacity() - strings[i].len())
+    else {
+        return String::new();
+    };
+    let len = strings.iter().map(String::len).fold(0, add_len);
+    let mut buf = std::mem::take(&mut strings[index]);
+    buf.reserve(len - buf.len());
+
+    if index > 0 {
+        let mut bytes = buf.into_bytes();
+        let own_len = bytes.len();
+        for s in &strings[..index] {
+            bytes.extend_from_slice(s.as_bytes());
+        }
+        let before_len = bytes.len() - own_len;
+        bytes.rotate_right(before_len);
+        // SAFETY: The bytes are whole strings, only rotated at the boundary
+        // between them, so they are still valid UTF-8.
+        buf = unsafe { String::from_utf8_unchecked(bytes) };
+    }
+    for s in &strings[index + 1..] {
+        buf.push_str(s);
+    }
+    buf
+}
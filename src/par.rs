@@ -0,0 +1,52 @@
+use rayon::prelude::*;
+
+use crate::len::add_len;
+
+/// Concatenate the pieces with one allocation, copying them into their
+/// places in parallel on the rayon thread pool.
+///
+/// It pays off for many large pieces, such as merging log shards or chunked
+/// files, while [`str_cat`](crate::str_cat) is faster for small ones.
+///
+/// Requires the `rayon` feature.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::par_concat;
+///
+/// let shards: Vec<String> = (0..100).map(|i| format!("line {i}\n")).collect();
+/// let s = par_concat(&shards);
+/// assert_eq!(s, shards.concat());
+/// assert_eq!(s.capacity(), s.len());
+/// ```
+pub fn par_concat<S: AsRef<str> + Sync>(pieces: &[S]) -> String {
+    // Borrow every piece once, so that its length can't change between
+    // counting and copying.
+    let pieces: Vec<&str> = pieces.iter().map(AsRef::as_ref).collect();
+    let len = pieces.iter().map(|piece| piece.len()).fold(0, add_len);
+    let mut bytes = Vec::with_capacity(len);
+
+    let mut rest = &mut bytes.spare_capacity_mut()[..len];
+    let mut slots = Vec::with_capacity(pieces.len());
+    for piece in &pieces {
+        let (slot, tail) = std::mem::take(&mut rest).split_at_mut(piece.len());
+        slots.push(slot);
+        rest = tail;
+    }
+    pieces.par_iter().zip(slots).for_each(|(piece, slot)| {
+        // SAFETY: `slot` was split off to be exactly as long as `piece`, and
+        // the spare capacity it borrows doesn't overlap with any piece.
+        unsafe {
+            std::ptr::copy_nonoverlapping(piece.as_ptr(), slot.as_mut_ptr().cast(), piece.len());
+        }
+    });
+
+    // SAFETY: The slots cover the first `len` bytes of the spare capacity
+    // without gaps, and every one of them has been initialized with a whole
+    // `&str`, so the bytes are initialized and valid UTF-8 once joined.
+    unsafe {
+        bytes.set_len(len);
+        String::from_utf8_unchecked(bytes)
+    }
+}
@@ -0,0 +1,12 @@
+//! ASCII sets for the `@enc(piece, set)` element. Requires the
+//! `percent-encoding` feature.
+
+pub use percent_encoding::{AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+
+/// Everything except the unreserved characters of RFC 3986, which are ASCII
+/// alphanumerics and `-`, `.`, `_`, `~`. This is safe for any URL component.
+pub const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
@@ -0,0 +1,132 @@
+use crate::percent::COMPONENT;
+use crate::piece::Piece;
+use percent_encoding::{utf8_percent_encode, AsciiSet};
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A piece percent-encoded with an [`AsciiSet`](crate::percent::AsciiSet).
+pub struct PercentEncoded<'a> {
+    value: &'a str,
+    set: &'static AsciiSet,
+    len: usize,
+}
+
+impl<'a> PercentEncoded<'a> {
+    #[inline]
+    pub fn new(value: &'a str, set: &'static AsciiSet) -> Self {
+        Self {
+            value,
+            set,
+            len: utf8_percent_encode(value, set).map(str::len).sum(),
+        }
+    }
+}
+
+impl Piece for PercentEncoded<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        utf8_percent_encode(self.value, self.set).for_each(push_str)
+    }
+}
+
+/// A `key=value` pair of a query string, which is empty if the value is
+/// `None`.
+pub struct QueryPair<'a> {
+    key: PercentEncoded<'a>,
+    value: Option<PercentEncoded<'a>>,
+}
+
+impl<'a> QueryPair<'a> {
+    #[inline]
+    pub fn new(key: &'a str, value: Option<&'a str>) -> Self {
+        Self {
+            key: PercentEncoded::new(key, COMPONENT),
+            value: value.map(|value| PercentEncoded::new(value, COMPONENT)),
+        }
+    }
+}
+
+impl Piece for QueryPair<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.value
+            .as_ref()
+            .map_or(0, |value| self.key.len() + 1 + value.len())
+    }
+
+    fn emit<F: FnMut(&str)>(&self, mut push_str: F) {
+        if let Some(value) = &self.value {
+            self.key.emit(&mut push_str);
+            push_str("=");
+            value.emit(push_str);
+        }
+    }
+}
+
+/// A value of [`query_cat`](crate::query_cat), which is either string-like or
+/// an [`Option`] of it.
+pub trait QueryValue {
+    fn query_value(&self) -> Option<&str>;
+}
+
+impl QueryValue for str {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl QueryValue for String {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl QueryValue for Cow<'_, str> {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl QueryValue for Box<str> {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl QueryValue for Rc<str> {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl QueryValue for Arc<str> {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T: QueryValue + ?Sized> QueryValue for &T {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        (**self).query_value()
+    }
+}
+
+impl<T: QueryValue> QueryValue for Option<T> {
+    #[inline]
+    fn query_value(&self) -> Option<&str> {
+        self.as_ref().and_then(QueryValue::query_value)
+    }
+}
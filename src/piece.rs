@@ -0,0 +1,318 @@
+use std::ffi::OsStr;
+use std::fmt::{self, Display};
+
+/// A piece that takes more than a plain `push_str` to be written, such as a
+/// padded one.
+pub trait Piece {
+    /// The number of bytes to be written, counted into the capacity. It may
+    /// be 0 for a piece that is only measured while being written, in which
+    /// case `is_empty` must be overridden.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write the piece by calling `push_str` one or more times.
+    fn emit<F: FnMut(&str)>(&self, push_str: F);
+}
+
+impl Piece for str {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, mut push_str: F) {
+        push_str(self)
+    }
+}
+
+impl<T: Piece + ?Sized> Piece for &T {
+    #[inline]
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        (**self).emit(push_str)
+    }
+}
+
+/// Alignment of a padded piece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// A piece padded with a fill character up to a minimum width.
+pub struct Pad<'a> {
+    value: &'a str,
+    fill: char,
+    before: usize,
+    after: usize,
+}
+
+impl<'a> Pad<'a> {
+    /// Pad `value` whose width is already measured as `value_width`.
+    pub fn new(value: &'a str, fill: char, align: Align, width: usize, value_width: usize) -> Self {
+        let padding = width.saturating_sub(value_width);
+        let (before, after) = match align {
+            Align::Left => (0, padding),
+            Align::Right => (padding, 0),
+            Align::Center => (padding / 2, padding - padding / 2),
+        };
+        Self {
+            value,
+            fill,
+            before,
+            after,
+        }
+    }
+}
+
+impl Piece for Pad<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.value.len() + (self.before + self.after) * self.fill.len_utf8()
+    }
+
+    fn emit<F: FnMut(&str)>(&self, mut push_str: F) {
+        let mut buf = [0; 4];
+        let fill = &*self.fill.encode_utf8(&mut buf);
+        for _ in 0..self.before {
+            push_str(fill);
+        }
+        push_str(self.value);
+        for _ in 0..self.after {
+            push_str(fill);
+        }
+    }
+}
+
+/// Ways to measure the width of a piece for padding.
+pub mod measure {
+    /// The number of `char`s, like [`format!`](format) does.
+    #[inline]
+    pub fn chars(s: &str) -> usize {
+        s.chars().count()
+    }
+
+    /// The number of columns taken in a terminal.
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn cols(s: &str) -> usize {
+        unicode_width::UnicodeWidthStr::width(s)
+    }
+
+    /// The number of `char`s, leaving out SGR escape sequences such as
+    /// `"\x1b[1;31m"`.
+    #[cfg(feature = "ansi")]
+    #[inline]
+    pub fn ansi(s: &str) -> usize {
+        visible(s).map(chars).sum()
+    }
+
+    /// The number of columns taken in a terminal, leaving out SGR escape
+    /// sequences.
+    #[cfg(all(feature = "ansi", feature = "unicode-width"))]
+    #[inline]
+    pub fn ansi_cols(s: &str) -> usize {
+        visible(s).map(cols).sum()
+    }
+
+    /// The parts of `s` between SGR escape sequences.
+    #[cfg(feature = "ansi")]
+    fn visible(mut s: &str) -> impl Iterator<Item = &str> {
+        std::iter::from_fn(move || {
+            if s.is_empty() {
+                return None;
+            }
+            let mut from = 0;
+            while let Some(esc) = s[from..].find("\x1b[").map(|i| from + i) {
+                let params = s[esc + 2..]
+                    .bytes()
+                    .take_while(|b| b.is_ascii_digit() || *b == b';' || *b == b':')
+                    .count();
+                if s.as_bytes().get(esc + 2 + params) == Some(&b'm') {
+                    let part = &s[..esc];
+                    s = &s[esc + 3 + params..];
+                    return Some(part);
+                }
+                from = esc + 1;
+            }
+            Some(std::mem::take(&mut s))
+        })
+    }
+}
+
+/// An `OsStr` written as UTF-8, with every invalid sequence replaced by
+/// U+FFFD like `String::from_utf8_lossy`.
+pub struct Lossy<'a> {
+    value: &'a [u8],
+    len: usize,
+}
+
+impl<'a> Lossy<'a> {
+    #[inline]
+    pub fn new(value: &'a OsStr) -> Self {
+        let value = value.as_encoded_bytes();
+        let len = value
+            .utf8_chunks()
+            .map(|chunk| chunk.valid().len() + if chunk.invalid().is_empty() { 0 } else { 3 })
+            .sum();
+        Self { value, len }
+    }
+}
+
+impl Piece for Lossy<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn emit<F: FnMut(&str)>(&self, mut push_str: F) {
+        for chunk in self.value.utf8_chunks() {
+            push_str(chunk.valid());
+            if !chunk.invalid().is_empty() {
+                push_str("\u{fffd}");
+            }
+        }
+    }
+}
+
+/// A value written through its `Display` implementation, which is measured by
+/// formatting it once more up front.
+pub struct Displayed<'a, T: ?Sized> {
+    value: &'a T,
+    len: usize,
+}
+
+impl<'a, T: Display + ?Sized> Displayed<'a, T> {
+    #[inline]
+    pub fn new(value: &'a T) -> Self {
+        let mut len = 0;
+        write_display(value, |s| len += s.len());
+        Self { value, len }
+    }
+}
+
+impl<T: Display + ?Sized> Piece for Displayed<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        write_display(self.value, push_str);
+    }
+}
+
+pub(crate) fn write_display<T: Display + ?Sized, F: FnMut(&str)>(value: &T, push_str: F) {
+    struct Writer<F>(F);
+
+    impl<F: FnMut(&str)> fmt::Write for Writer<F> {
+        #[inline]
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            (self.0)(s);
+            Ok(())
+        }
+    }
+
+    fmt::Write::write_fmt(&mut Writer(push_str), format_args!("{value}"))
+        .expect("a Display implementation returned an error unexpectedly");
+}
+
+/// A value written through its `Display` implementation without being
+/// measured, so it is left out of the capacity and may grow the buffer.
+pub struct Streamed<'a, T: ?Sized> {
+    value: &'a T,
+}
+
+impl<'a, T: Display + ?Sized> Streamed<'a, T> {
+    #[inline]
+    pub fn new(value: &'a T) -> Self {
+        Self { value }
+    }
+}
+
+impl<T: Display + ?Sized> Piece for Streamed<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        write_display(self.value, push_str);
+        crate::len::note_uncounted();
+    }
+}
+
+/// Chars from an iterator, which is cloned to be written, so that it can be
+/// replayed, while only the lower bound of its size hint is counted into the
+/// capacity.
+pub struct Chars<I> {
+    iter: I,
+}
+
+impl<I: Iterator<Item = char> + Clone> Chars<I> {
+    #[inline]
+    pub fn new(iter: I) -> Self {
+        Self { iter }
+    }
+}
+
+impl<I: Iterator<Item = char> + Clone> Piece for Chars<I> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.size_hint().0
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.iter.size_hint().1 == Some(0)
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        push_chars(self.iter.clone(), push_str);
+        crate::len::note_uncounted();
+    }
+}
+
+/// Write `chars` through a small buffer so that `push_str` isn't called for
+/// every char.
+pub(crate) fn push_chars<I, F>(chars: I, mut push_str: F)
+where
+    I: IntoIterator<Item = char>,
+    F: FnMut(&str),
+{
+    let mut buf = [0; 64];
+    let mut filled = 0;
+    for c in chars {
+        if filled + 4 > buf.len() {
+            push_str(std::str::from_utf8(&buf[..filled]).unwrap());
+            filled = 0;
+        }
+        filled += c.encode_utf8(&mut buf[filled..]).len();
+    }
+    if filled > 0 {
+        push_str(std::str::from_utf8(&buf[..filled]).unwrap());
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// A pool of reusable [`String`](String) buffers, which can be shared
+/// between threads.
+///
+/// # Example
+///
+/// ```
+/// use str_cat::{str_cat, StringPool};
+///
+/// let pool = StringPool::new();
+/// let ptr = {
+///     let s = str_cat!(pool.get(); "Hello", " ", "World!");
+///     assert_eq!(*s, "Hello World!");
+///     s.as_ptr()
+/// };
+///
+/// // The buffer is cleared and reused.
+/// let mut s = pool.get();
+/// assert!(s.is_empty());
+/// str_cat!(&mut *s; "Hi");
+/// assert_eq!(s.as_ptr(), ptr);
+/// ```
+#[derive(Default)]
+pub struct StringPool {
+    buffers: Mutex<Vec<String>>,
+}
+
+impl StringPool {
+    /// Create an empty pool.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take an empty buffer from the pool, or a new one if there is none,
+    /// which goes back to the pool when dropped.
+    pub fn get(&self) -> PooledString<'_> {
+        let buf = self.lock().pop().unwrap_or_default();
+        PooledString { pool: self, buf }
+    }
+
+    /// The number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether there is no buffer in the pool.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<String>> {
+        // The buffers are valid even if a thread panicked while holding the
+        // lock.
+        self.buffers.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl fmt::Debug for StringPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StringPool")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// A buffer taken from a [`StringPool`](StringPool), which dereferences to
+/// [`String`](String).
+pub struct PooledString<'a> {
+    pool: &'a StringPool,
+    buf: String,
+}
+
+impl PooledString<'_> {
+    /// Take the string out of the pool for good.
+    pub fn into_inner(mut self) -> String {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+impl Drop for PooledString<'_> {
+    fn drop(&mut self) {
+        if self.buf.capacity() > 0 {
+            let mut buf = std::mem::take(&mut self.buf);
+            buf.clear();
+            self.pool.lock().push(buf);
+        }
+    }
+}
+
+impl Deref for PooledString<'_> {
+    type Target = String;
+
+    #[inline]
+    fn deref(&self) -> &String {
+        &self.buf
+    }
+}
+
+impl DerefMut for PooledString<'_> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut String {
+        &mut self.buf
+    }
+}
+
+impl fmt::Display for PooledString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.buf, f)
+    }
+}
+
+impl fmt::Debug for PooledString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.buf, f)
+    }
+}
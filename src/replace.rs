@@ -0,0 +1,46 @@
+use crate::len::{add_len, mul_len};
+use crate::piece::Piece;
+
+/// A piece with every match of a literal pattern replaced while being
+/// copied, like [`str::replace`](str::replace) without the temporary
+/// `String`.
+pub struct Replaced<'a> {
+    value: &'a str,
+    from: &'a str,
+    to: &'a str,
+    len: usize,
+}
+
+impl<'a> Replaced<'a> {
+    #[inline]
+    pub fn new(value: &'a str, from: &'a str, to: &'a str) -> Self {
+        let matches = value.matches(from).count();
+        let len = add_len(
+            value.len() - matches * from.len(),
+            mul_len(to.len(), matches),
+        );
+        Self {
+            value,
+            from,
+            to,
+            len,
+        }
+    }
+}
+
+impl Piece for Replaced<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn emit<F: FnMut(&str)>(&self, mut push_str: F) {
+        let mut last = 0;
+        for (start, matched) in self.value.match_indices(self.from) {
+            push_str(&self.value[last..start]);
+            push_str(self.to);
+            last = start + matched.len();
+        }
+        push_str(&self.value[last..]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::cell::Cell;
+use std::fmt;
+use std::ops::Deref;
+
+thread_local! {
+    static BUFFER: Cell<String> = const { Cell::new(String::new()) };
+}
+
+/// A string built into the thread-local buffer of
+/// [`scratch_cat`](crate::scratch_cat), which is handed back for reuse when
+/// dropped.
+///
+/// The buffer is taken out while the guard is alive, so a nested scratch
+/// string gets a fresh one, and the larger one is kept when both are handed
+/// back.
+pub struct Scratch {
+    buf: String,
+}
+
+impl Scratch {
+    #[doc(hidden)]
+    #[inline]
+    pub fn take() -> Self {
+        let buf = BUFFER.try_with(Cell::take).unwrap_or_default();
+        Self { buf }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.buf.push_str(s);
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(additional);
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// The built string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.buf
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        let _ = BUFFER.try_with(|cell| {
+            let other = cell.take();
+            cell.set(if other.capacity() > buf.capacity() {
+                other
+            } else {
+                buf
+            });
+        });
+    }
+}
+
+impl Deref for Scratch {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.buf
+    }
+}
+
+impl AsRef<str> for Scratch {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.buf
+    }
+}
+
+impl fmt::Display for Scratch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.buf, f)
+    }
+}
+
+impl fmt::Debug for Scratch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.buf, f)
+    }
+}
+
+impl PartialEq<str> for Scratch {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        *self.buf == *other
+    }
+}
+
+impl PartialEq<&str> for Scratch {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        *self.buf == **other
+    }
+}
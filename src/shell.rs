@@ -0,0 +1,95 @@
+use crate::piece::Piece;
+
+/// Write `s` quoted for a POSIX shell.
+///
+/// Words made only of characters that are never special are written as is,
+/// anything else is wrapped in single quotes, with every `'` written as
+/// `'\''`.
+#[cfg(not(windows))]
+fn quote<F: FnMut(&str)>(s: &str, mut push_str: F) {
+    let is_plain = |b: u8| b.is_ascii_alphanumeric() || b"%+,-./:=@_".contains(&b);
+    if !s.is_empty() && s.bytes().all(is_plain) {
+        push_str(s);
+        return;
+    }
+
+    push_str("'");
+    let mut parts = s.split('\'');
+    if let Some(first) = parts.next() {
+        push_str(first);
+    }
+    for part in parts {
+        push_str("'\\''");
+        push_str(part);
+    }
+    push_str("'");
+}
+
+/// Write `s` quoted as a single argument for `CommandLineToArgvW` and the
+/// MSVC runtime.
+///
+/// Words without whitespace or `"` are written as is, anything else is
+/// wrapped in double quotes, escaping `"` and the backslashes before it and
+/// before the closing quote.
+///
+/// The metacharacters of `cmd.exe` are left alone, so the result must not be
+/// passed to `cmd /C`.
+#[cfg(windows)]
+fn quote<F: FnMut(&str)>(s: &str, mut push_str: F) {
+    fn push_backslashes<F: FnMut(&str)>(n: usize, push_str: &mut F) {
+        for _ in 0..n {
+            push_str("\\");
+        }
+    }
+
+    if !s.is_empty() && !s.contains([' ', '\t', '\n', '\x0b', '"']) {
+        push_str(s);
+        return;
+    }
+
+    push_str("\"");
+    let mut run_start = 0;
+    let mut backslashes = 0;
+    for (i, b) in s.bytes().enumerate() {
+        match b {
+            b'\\' => backslashes += 1,
+            b'"' => {
+                push_str(&s[run_start..i]);
+                push_backslashes(backslashes + 1, &mut push_str);
+                run_start = i;
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+    }
+    push_str(&s[run_start..]);
+    push_backslashes(backslashes, &mut push_str);
+    push_str("\"");
+}
+
+/// A `&str` quoted as a single shell word for the current platform.
+pub struct ShellQuoted<'a> {
+    value: &'a str,
+    len: usize,
+}
+
+impl<'a> ShellQuoted<'a> {
+    #[inline]
+    pub fn new(value: &'a str) -> Self {
+        let mut len = 0;
+        quote(value, |s| len += s.len());
+        Self { value, len }
+    }
+}
+
+impl Piece for ShellQuoted<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        quote(self.value, push_str);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::piece::Piece;
+
+/// A piece quoted for SQL, as an identifier or a string literal depending on
+/// the quote, with every quote in it doubled.
+pub struct SqlQuoted<'a> {
+    value: &'a str,
+    quote: char,
+    len: usize,
+}
+
+impl<'a> SqlQuoted<'a> {
+    #[inline]
+    pub fn new(value: &'a str, quote: char) -> Self {
+        let quotes = value.matches(quote).count();
+        let len = value.len() + (quotes + 2) * quote.len_utf8();
+        Self { value, quote, len }
+    }
+}
+
+impl Piece for SqlQuoted<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn emit<F: FnMut(&str)>(&self, mut push_str: F) {
+        let mut buf = [0; 4];
+        let quote = &*self.quote.encode_utf8(&mut buf);
+        push_str(quote);
+        let mut parts = self.value.split(self.quote);
+        if let Some(first) = parts.next() {
+            push_str(first);
+        }
+        for part in parts {
+            push_str(quote);
+            push_str(quote);
+            push_str(part);
+        }
+        push_str(quote);
+    }
+}
@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fmt;
+use std::path::{Component, Path};
+
+/// Tracks whether a component would replace the path built so far.
+pub struct StrictPath {
+    nonempty: bool,
+    invalid: bool,
+}
+
+impl StrictPath {
+    #[inline]
+    pub fn new(base: &Path) -> Self {
+        Self {
+            nonempty: !base.as_os_str().is_empty(),
+            invalid: false,
+        }
+    }
+
+    /// Check `component`, which is about to be pushed.
+    #[inline]
+    pub fn check(&mut self, component: &Path) {
+        let replaces = component.has_root()
+            || matches!(component.components().next(), Some(Component::Prefix(_)));
+        self.invalid |= self.nonempty && replaces;
+        self.nonempty |= !component.as_os_str().is_empty();
+    }
+
+    #[inline]
+    pub fn result(&self) -> Result<(), StrictPathError> {
+        if self.invalid {
+            Err(StrictPathError)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The error of [`strict_path_cat`](crate::strict_path_cat) when a component
+/// is absolute or rooted, in which case the target is left untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrictPathError;
+
+impl fmt::Display for StrictPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("absolute path component would replace the path")
+    }
+}
+
+impl Error for StrictPathError {}
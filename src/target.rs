@@ -0,0 +1,83 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// The methods of a [`String`](String) target on a wrapper of one, which is
+/// only made mutable when it is actually written.
+///
+/// It is only in scope inside the macros.
+pub trait StringTarget {
+    fn reserve(&mut self, additional: usize);
+
+    fn push_str(&mut self, s: &str);
+
+    fn capacity(&self) -> usize;
+}
+
+/// Cloned by `make_mut` on the first write if it is not unique.
+impl StringTarget for Arc<String> {
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        Arc::make_mut(self).reserve(additional);
+    }
+
+    #[inline]
+    fn push_str(&mut self, s: &str) {
+        Arc::make_mut(self).push_str(s);
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        String::capacity(self)
+    }
+}
+
+/// Cloned by `make_mut` on the first write if it is not unique.
+impl StringTarget for Rc<String> {
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        Rc::make_mut(self).reserve(additional);
+    }
+
+    #[inline]
+    fn push_str(&mut self, s: &str) {
+        Rc::make_mut(self).push_str(s);
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        String::capacity(self)
+    }
+}
+
+/// Left borrowed unless something non-empty is pushed, and copied into a
+/// `String` with room for the pushes otherwise.
+impl StringTarget for Cow<'_, str> {
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        match self {
+            _ if additional == 0 => {}
+            Cow::Borrowed(s) => {
+                let mut owned = String::with_capacity(crate::len::add_len(s.len(), additional));
+                owned.push_str(s);
+                *self = Cow::Owned(owned);
+            }
+            Cow::Owned(s) => s.reserve(additional),
+        }
+    }
+
+    #[inline]
+    fn push_str(&mut self, s: &str) {
+        if !s.is_empty() {
+            self.to_mut().push_str(s);
+        }
+    }
+
+    #[inline]
+    fn capacity(&self) -> usize {
+        match self {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(s) => s.capacity(),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::piece::Piece;
+
+/// Where the parts of a URL go for [`url_cat`](crate::url_cat), which is
+/// found once from the URL before pushing anything.
+///
+/// The query and the fragment are kept as lengths from the end of the URL,
+/// so that they stay put while pushing before them.
+pub struct UrlQuery {
+    /// The separator due before the next pair, which is `None` right after a
+    /// `?` or `&`.
+    sep: Option<char>,
+    /// How long the query and the fragment are together, which path segments
+    /// go before.
+    query_tail: usize,
+    /// How long the fragment is, which query pairs go before.
+    fragment_tail: usize,
+}
+
+impl UrlQuery {
+    #[inline]
+    pub fn new(url: &str) -> Self {
+        let fragment = url.find('#').unwrap_or(url.len());
+        let before = &url[..fragment];
+        let query = before.find('?');
+        let sep = match query {
+            None => Some('?'),
+            Some(_) if before.ends_with(['?', '&']) => None,
+            Some(_) => Some('&'),
+        };
+        Self {
+            sep,
+            query_tail: url.len() - query.unwrap_or(fragment),
+            fragment_tail: url.len() - fragment,
+        }
+    }
+}
+
+/// Pushing the parts of a URL for [`url_cat`](crate::url_cat), as methods so
+/// that the target can be either a `String` or a `&mut String`.
+pub trait UrlString {
+    /// Push a path segment before the query and the fragment, joined to the
+    /// path with exactly one `/`.
+    ///
+    /// Only the leading slashes of `segment` are dropped, so that a trailing
+    /// slash of the last segment is kept. An empty segment is left out. The
+    /// first segment of an empty URL is the base, which is taken whole and
+    /// looked up for a query and a fragment.
+    fn push_url_segment(&mut self, query: &mut UrlQuery, segment: &str);
+
+    /// Push a path segment which is a piece, such as a percent-encoded one,
+    /// joined like a plain one but without dropping anything from it.
+    fn push_url_segment_piece<P: Piece + ?Sized>(&mut self, query: &mut UrlQuery, segment: &P);
+
+    /// Push a `key=value` pair of the query, with the separator due in
+    /// `query`, and before the fragment if the URL has one. A pair whose
+    /// value is `None` is left out.
+    fn push_url_query_pair<P: Piece + ?Sized>(&mut self, query: &mut UrlQuery, pair: &P);
+
+    /// Push the fragment after a `#`, replacing the one the URL has already.
+    fn push_url_fragment<P: Piece + ?Sized>(&mut self, query: &mut UrlQuery, fragment: &P);
+}
+
+impl UrlString for String {
+    fn push_url_segment(&mut self, query: &mut UrlQuery, segment: &str) {
+        if self.is_empty() {
+            self.push_str(segment);
+            *query = UrlQuery::new(self);
+            return;
+        }
+        let segment = segment.trim_start_matches('/');
+        if !segment.is_empty() {
+            let mut at = self.len() - query.query_tail;
+            if !self[..at].ends_with('/') {
+                self.insert(at, '/');
+                at += 1;
+            }
+            self.insert_str(at, segment);
+        }
+    }
+
+    fn push_url_segment_piece<P: Piece + ?Sized>(&mut self, query: &mut UrlQuery, segment: &P) {
+        if segment.is_empty() {
+            return;
+        }
+        let mut at = self.len() - query.query_tail;
+        if at != 0 && !self[..at].ends_with('/') {
+            self.insert(at, '/');
+            at += 1;
+        }
+        segment.emit(|s| {
+            self.insert_str(at, s);
+            at += s.len();
+        });
+    }
+
+    fn push_url_query_pair<P: Piece + ?Sized>(&mut self, query: &mut UrlQuery, pair: &P) {
+        if pair.is_empty() {
+            return;
+        }
+        let before = self.len();
+        let mut at = before - query.fragment_tail;
+        if let Some(sep) = query.sep.replace('&') {
+            self.insert(at, sep);
+            at += 1;
+        }
+        pair.emit(|s| {
+            self.insert_str(at, s);
+            at += s.len();
+        });
+        query.query_tail += self.len() - before;
+    }
+
+    fn push_url_fragment<P: Piece + ?Sized>(&mut self, query: &mut UrlQuery, fragment: &P) {
+        self.truncate(self.len() - query.fragment_tail);
+        query.query_tail -= query.fragment_tail;
+        let before = self.len();
+        self.push('#');
+        fragment.emit(|s| self.push_str(s));
+        query.fragment_tail = self.len() - before;
+        query.query_tail += query.fragment_tail;
+    }
+}
@@ -0,0 +1,43 @@
+use std::char::{decode_utf16, DecodeUtf16Error, REPLACEMENT_CHARACTER};
+
+use crate::piece::{push_chars, Piece};
+
+/// UTF-16 decoded while being copied, with every unpaired surrogate replaced
+/// by U+FFFD like `String::from_utf16_lossy`.
+pub struct Utf16<'a> {
+    value: &'a [u16],
+    len: usize,
+}
+
+impl<'a> Utf16<'a> {
+    #[inline]
+    pub fn lossy(value: &'a [u16]) -> Self {
+        let len = decode_utf16(value.iter().copied())
+            .map(|c| c.unwrap_or(REPLACEMENT_CHARACTER).len_utf8())
+            .sum();
+        Self { value, len }
+    }
+
+    /// Fail on the first unpaired surrogate, like `String::from_utf16`.
+    #[inline]
+    pub fn strict(value: &'a [u16]) -> Result<Self, DecodeUtf16Error> {
+        let mut len = 0;
+        for c in decode_utf16(value.iter().copied()) {
+            len += c?.len_utf8();
+        }
+        Ok(Self { value, len })
+    }
+}
+
+impl Piece for Utf16<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn emit<F: FnMut(&str)>(&self, push_str: F) {
+        let chars = decode_utf16(self.value.iter().copied());
+        push_chars(chars.map(|c| c.unwrap_or(REPLACEMENT_CHARACTER)), push_str);
+    }
+}